@@ -0,0 +1,476 @@
+//! The state of a game and how it reacts to key presses.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use sudoku::{House, Sudoku, NUM_SQUARES};
+use sudoku::explain::{cell_name, English, StepFormatter};
+use sudoku::game::{Game, GameError};
+use sudoku::hint::{Hint, HintStage};
+use sudoku::logic::{LogicalSolver, Technique};
+
+use std::time::{Duration, Instant};
+
+/// What the digit keys do.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Mode {
+    /// Enter values.
+    Values,
+    /// Toggle pencil marks.
+    PencilMarks,
+}
+
+/// A [Game] in progress and the state of the user interface around it.
+#[derive(Clone, Debug)]
+pub(crate) struct App {
+    game: Game,
+    cursor: (usize, usize),
+    mode: Mode,
+    /// The hint revealed so far and the grid it was found for.
+    hint: Option<(Sudoku, Hint)>,
+    /// The squares the last hint points to, highlighted until the next key
+    /// press.
+    highlight: Vec<(usize, usize)>,
+    message: Option<String>,
+    started: Instant,
+    /// How long it took to solve the puzzle, once it is solved.
+    solved_after: Option<Duration>,
+    quit: bool,
+}
+
+impl App {
+
+    /// Start a new game. Return an error if `puzzle` does not have exactly
+    /// one solution.
+    pub(crate) fn new(puzzle: Sudoku) -> Result<App, GameError> {
+        Ok(App {
+            game: Game::new(puzzle)?,
+            cursor: (0, 0),
+            mode: Mode::Values,
+            hint: None,
+            highlight: Vec::new(),
+            message: None,
+            started: Instant::now(),
+            solved_after: None,
+            quit: false,
+        })
+    }
+
+    pub(crate) fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub(crate) fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    pub(crate) fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub(crate) fn highlight(&self) -> &[(usize, usize)] {
+        &self.highlight
+    }
+
+    pub(crate) fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Get the time spent on the puzzle, which stops once it is solved.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.solved_after.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    pub(crate) fn is_solved(&self) -> bool {
+        self.solved_after.is_some()
+    }
+
+    pub(crate) fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// React to a key press.
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) {
+        self.highlight.clear();
+        self.message = None;
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ if self.is_solved() => self.message = Some("The puzzle is solved, press q to quit.".to_owned()),
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(8, 0),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(0, 8),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(0, 1),
+            KeyCode::Char(digit @ '1'..='9') => {
+                let value = digit.to_digit(10).expect("digit is between 1 and 9");
+                match self.mode {
+                    Mode::Values => self.enter(value),
+                    Mode::PencilMarks => self.toggle_pencil_mark(value),
+                }
+            },
+            KeyCode::Char('0' | '.') | KeyCode::Backspace | KeyCode::Delete => self.clear(),
+            KeyCode::Char('p') => {
+                self.mode = match self.mode {
+                    Mode::Values => Mode::PencilMarks,
+                    Mode::PencilMarks => Mode::Values,
+                };
+            },
+            KeyCode::Char('a') => self.game.fill_pencil_marks(),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') => self.redo(),
+            KeyCode::Char('b') => {
+                let undone = self.game.undo_to_last_consistent();
+                self.message = Some(format!("Undid {} edit(s) to get rid of all mistakes.", undone));
+            },
+            KeyCode::Char('?') => self.give_hint(),
+            _ => {},
+        }
+    }
+
+    /// Move the cursor by (`dx` / `dy`), wrapping around at the edges.
+    fn move_cursor(&mut self, dx: usize, dy: usize) {
+        let (x, y) = self.cursor;
+        self.cursor = ((x + dx) % 9, (y + dy) % 9);
+    }
+
+    /// Enter `value` into the square under the cursor.
+    fn enter(&mut self, value: u32) {
+        let (x, y) = self.cursor;
+        if self.game.is_given(x, y) {
+            self.message = Some("Givens cannot be changed.".to_owned());
+            return;
+        }
+
+        self.place(x, y, value);
+    }
+
+    /// Place `value` on the square at (`x` / `y`), which must not be a given,
+    /// and remove it from the pencil marks of the squares sharing a house with
+    /// it.
+    fn place(&mut self, x: usize, y: usize, value: u32) {
+        // one edit, so undo brings back the pencil marks as well
+        self.game.group_edits(|game| {
+            game.set_value(x, y, value).expect("the square is not a given");
+
+            for house in [House::Row(y), House::Column(x), House::box_containing(x, y)] {
+                for (peer_x, peer_y) in house.squares() {
+                    if !game.is_given(peer_x, peer_y) {
+                        game.set_pencil_mark(peer_x, peer_y, value, false).expect("the square is not a given");
+                    }
+                }
+            }
+        });
+
+        if self.game.is_solved() {
+            let elapsed = self.started.elapsed();
+            self.solved_after = Some(elapsed);
+            self.message = Some(format!("Solved in {}!", format_duration(elapsed)));
+        }
+    }
+
+    /// Toggle the pencil mark for `value` in the square under the cursor.
+    fn toggle_pencil_mark(&mut self, value: u32) {
+        let (x, y) = self.cursor;
+        if self.game.grid().get_value(x, y) != 0 {
+            self.message = Some("Pencil marks can only be made in empty squares.".to_owned());
+            return;
+        }
+
+        self.game.toggle_pencil_mark(x, y, value).expect("the square is empty");
+    }
+
+    /// Clear the value in the square under the cursor, or its pencil marks
+    /// if it is empty.
+    fn clear(&mut self) {
+        let (x, y) = self.cursor;
+        if self.game.is_given(x, y) {
+            self.message = Some("Givens cannot be changed.".to_owned());
+        } else if self.game.grid().get_value(x, y) != 0 {
+            self.game.clear_value(x, y).expect("the square is not a given");
+        } else {
+            self.game.clear_pencil_marks(x, y);
+        }
+    }
+
+    fn undo(&mut self) {
+        if !self.game.undo() {
+            self.message = Some("Nothing to undo.".to_owned());
+        }
+    }
+
+    fn redo(&mut self) {
+        if !self.game.redo() {
+            self.message = Some("Nothing to redo.".to_owned());
+        }
+    }
+
+    /// Point out a mistake, or reveal the next stage of a hint at a single
+    /// that can be found on the grid: first the region to look at, then the
+    /// technique and finally the value, which is placed.
+    ///
+    /// The hint is kept until it is fully revealed or the grid changes. If
+    /// there is no single, the value under the cursor (or in the first empty
+    /// square) is taken from the solution instead.
+    fn give_hint(&mut self) {
+        if let Some(&(x, y)) = self.game.mistakes().first() {
+            self.cursor = (x, y);
+            self.highlight = vec![(x, y)];
+            self.message = Some(format!("The value in {} is wrong.", cell_name(x, y)));
+            return;
+        }
+
+        let grid = *self.game.grid();
+        match &mut self.hint {
+            Some((hint_grid, hint)) if *hint_grid == grid => {
+                hint.reveal();
+            },
+            _ => {
+                // Only singles follow from the values on the grid alone,
+                // other techniques remove candidates the player may not have
+                // pencil marked.
+                let solver = LogicalSolver::new(&grid);
+                let step = [Technique::HiddenSingle, Technique::NakedSingle]
+                    .into_iter()
+                    .find_map(|technique| solver.find(technique));
+                self.hint = step.map(|step| (grid, Hint::new(step)));
+            },
+        }
+
+        let Some((_, hint)) = self.hint.clone() else {
+            self.reveal_solution();
+            return;
+        };
+
+        let region = hint
+            .region()
+            .iter()
+            .map(|house| house.to_string())
+            .collect::<Vec<_>>()
+            .join(" and ");
+        self.highlight = hint.region().iter().flat_map(|house| house.squares()).collect();
+
+        match (hint.stage(), hint.technique(), hint.step()) {
+            (HintStage::Answer, _, Some(step)) => {
+                let placement = step.placements[0];
+                self.hint = None;
+                self.place_hint(placement.x, placement.y, placement.value, format!("Hint: {}", English.format_step(step)));
+            },
+            (HintStage::Technique, Some(technique), _) => {
+                self.message = Some(format!("Hint: look for a {} in {}. Press ? again for the answer.", technique, region));
+            },
+            _ => {
+                self.message = Some(format!("Hint: take a look at {}. Press ? again for the technique.", region));
+            },
+        }
+    }
+
+    /// Place the value of the solution under the cursor, or in the first
+    /// empty square if the square under the cursor is not empty.
+    fn reveal_solution(&mut self) {
+        let grid = self.game.grid();
+        let (x, y) = if grid.get_value(self.cursor.0, self.cursor.1) == 0 {
+            self.cursor
+        } else {
+            match (0..NUM_SQUARES).map(|square| (square % 9, square / 9)).find(|&(x, y)| grid.get_value(x, y) == 0) {
+                Some(square) => square,
+                None => return,
+            }
+        };
+
+        let value = self.game.solution().get_value(x, y);
+        self.place_hint(x, y, value, format!("Hint (the solution): {} goes in {}.", value, cell_name(x, y)));
+    }
+
+    /// Place the `value` of a hint on the square at (`x` / `y`) and explain
+    /// it with `message`, unless the puzzle is solved by it.
+    fn place_hint(&mut self, x: usize, y: usize, value: u32, message: String) {
+        self.cursor = (x, y);
+        self.highlight = vec![(x, y)];
+        self.place(x, y, value);
+        if !self.is_solved() {
+            self.message = Some(message);
+        }
+    }
+}
+
+/// Format `duration` as minutes and seconds.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    use sudoku::Sudoku;
+
+    use crate::tests::PUZZLE;
+
+    use super::{App, Mode};
+
+    fn press(app: &mut App, keys: &str) {
+        for key in keys.chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(key)));
+        }
+    }
+
+    #[test]
+    fn rejects_puzzles_without_unique_solution() {
+        assert!(App::new(Sudoku::new_empty()).is_err());
+        assert!(App::new(PUZZLE.parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn moves_cursor_with_wrap_around() {
+        let mut app = App::new(PUZZLE.parse().unwrap()).unwrap();
+
+        app.handle_key(KeyEvent::from(KeyCode::Left));
+        assert_eq!(app.cursor(), (8, 0));
+
+        press(&mut app, "jjl");
+        assert_eq!(app.cursor(), (0, 2));
+    }
+
+    #[test]
+    fn givens_cannot_be_changed() {
+        let mut app = App::new(PUZZLE.parse().unwrap()).unwrap();
+
+        press(&mut app, "ll5");
+        assert_eq!(app.game().grid().get_value(2, 0), 1);
+
+        press(&mut app, "0");
+        assert_eq!(app.game().grid().get_value(2, 0), 1);
+        assert!(app.message().is_some());
+    }
+
+    #[test]
+    fn enters_and_clears_values() {
+        let mut app = App::new(PUZZLE.parse().unwrap()).unwrap();
+
+        press(&mut app, "7");
+        assert_eq!(app.game().grid().get_value(0, 0), 7);
+
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(app.game().grid().get_value(0, 0), 0);
+    }
+
+    #[test]
+    fn pencil_marks() {
+        let mut app = App::new(PUZZLE.parse().unwrap()).unwrap();
+
+        press(&mut app, "p38");
+        assert_eq!(app.mode(), Mode::PencilMarks);
+        assert!(app.game().has_pencil_mark(0, 0, 3));
+        assert!(app.game().has_pencil_mark(0, 0, 8));
+
+        press(&mut app, "3");
+        assert!(!app.game().has_pencil_mark(0, 0, 3));
+
+        // placing a value removes it from the pencil marks of its peers
+        press(&mut app, "pl8");
+        assert_eq!(app.game().grid().get_value(1, 0), 8);
+        assert!(!app.game().has_pencil_mark(0, 0, 8));
+
+        press(&mut app, "a");
+        assert!(app.game().has_pencil_mark(0, 0, 3));
+        assert!(!app.game().has_pencil_mark(0, 0, 1));
+    }
+
+    #[test]
+    fn hints_point_out_mistakes_first() {
+        let puzzle: Sudoku = PUZZLE.parse().unwrap();
+        let solution = puzzle.find_solution().unwrap();
+        let mut app = App::new(puzzle).unwrap();
+
+        let wrong = solution.get_value(0, 2) % 9 + 1;
+        press(&mut app, &format!("jj{}", wrong));
+        press(&mut app, "?");
+        assert_eq!(app.game().grid().get_value(0, 2), wrong);
+        assert_eq!(app.highlight(), [(0, 2)]);
+
+        press(&mut app, "0???");
+        let (x, y) = app.highlight()[0];
+        assert_eq!(app.game().grid().get_value(x, y), solution.get_value(x, y));
+    }
+
+    #[test]
+    fn hints_are_revealed_in_stages() {
+        let mut app = App::new(PUZZLE.parse().unwrap()).unwrap();
+
+        press(&mut app, "?");
+        assert_eq!(app.game().grid(), app.game().puzzle());
+        assert_eq!(app.highlight().len(), 9);
+        assert!(app.message().unwrap().contains("box 4"));
+
+        // moving around keeps the hint
+        press(&mut app, "l?");
+        assert_eq!(app.game().grid(), app.game().puzzle());
+        assert!(app.message().unwrap().contains("Hidden Single"));
+
+        press(&mut app, "?");
+        assert_eq!(app.game().grid().get_value(0, 3), 6);
+        assert_eq!(app.highlight(), [(0, 3)]);
+        assert_eq!(app.cursor(), (0, 3));
+        assert_eq!(app.message(), Some("Hint: Hidden single: 6 must go in r4c1 because it is the only place in box 4."));
+
+        // the next hint starts over
+        press(&mut app, "?");
+        assert!(app.message().unwrap().contains("take a look"));
+    }
+
+    #[test]
+    fn hints_need_a_single() {
+        // starts with a pointing step, which removes candidates only
+        let puzzle = "...1..584...9......43......1..5....8.5.......8.23..9..6....7..12....63....9.....7";
+        let mut app = App::new(puzzle.parse().unwrap()).unwrap();
+
+        press(&mut app, "?");
+        assert_eq!(app.message(), Some("Hint (the solution): 9 goes in r1c1."));
+        assert_eq!(app.game().grid().get_value(0, 0), 9);
+    }
+
+    #[test]
+    fn detects_completion() {
+        let mut app = App::new(PUZZLE.parse().unwrap()).unwrap();
+
+        while !app.is_solved() {
+            press(&mut app, "?");
+        }
+
+        assert!(app.game().grid().is_solved());
+
+        press(&mut app, "5");
+        assert!(app.game().grid().is_solved());
+        assert!(!app.should_quit());
+
+        press(&mut app, "q");
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn undo_redo_and_back_to_consistent() {
+        let puzzle: Sudoku = PUZZLE.parse().unwrap();
+        let solution = puzzle.find_solution().unwrap();
+        let mut app = App::new(puzzle).unwrap();
+
+        // a pencil mark in the same row is removed by entering the value
+        let value = solution.get_value(0, 0);
+        press(&mut app, &format!("lp{}ph{}", value, value));
+        assert!(!app.game().has_pencil_mark(1, 0, value));
+
+        press(&mut app, "u");
+        assert_eq!(app.game().grid().get_value(0, 0), 0);
+        assert!(app.game().has_pencil_mark(1, 0, value));
+
+        press(&mut app, "r");
+        assert_eq!(app.game().grid().get_value(0, 0), value);
+
+        let wrong = solution.get_value(1, 0) % 9 + 1;
+        press(&mut app, &format!("l{}b", wrong));
+        assert_eq!(app.game().grid().get_value(1, 0), 0);
+        assert_eq!(app.game().grid().get_value(0, 0), value);
+    }
+}
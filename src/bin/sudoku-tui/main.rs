@@ -0,0 +1,175 @@
+//! `sudoku-tui`, a terminal game to play Sudoku puzzles.
+//!
+//! Run `sudoku-tui --help` for the available options. The keys are shown
+//! below the grid while playing.
+
+mod app;
+mod ui;
+
+use clap::{Parser, ValueEnum};
+
+use crossterm::{cursor, execute};
+use crossterm::event::{self, Event, KeyEventKind};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+
+use sudoku::Sudoku;
+use sudoku::generator::Generator;
+use sudoku::rating::Difficulty;
+
+use app::App;
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// How often the screen is redrawn to update the clock if no key is pressed.
+const TICK: Duration = Duration::from_millis(500);
+
+/// Play Sudoku in the terminal.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    /// The puzzle to play as 81 characters, digits for clues and any of `0`,
+    /// `.`, `_` or `*` for empty squares. A random puzzle is generated if
+    /// missing.
+    puzzle: Option<String>,
+
+    /// The difficulty of the generated puzzle.
+    #[arg(short, long, value_enum, default_value_t = DifficultyArg::Medium, conflicts_with = "puzzle")]
+    difficulty: DifficultyArg,
+
+    /// Seed the random number generator to always generate the same puzzle.
+    #[arg(long, conflicts_with = "puzzle")]
+    seed: Option<u64>,
+}
+
+/// The command line names of the [Difficulty]s a puzzle can be generated
+/// with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum DifficultyArg {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl From<DifficultyArg> for Difficulty {
+    fn from(difficulty: DifficultyArg) -> Difficulty {
+        match difficulty {
+            DifficultyArg::Easy => Difficulty::Easy,
+            DifficultyArg::Medium => Difficulty::Medium,
+            DifficultyArg::Hard => Difficulty::Hard,
+            DifficultyArg::Expert => Difficulty::Expert,
+        }
+    }
+}
+
+/// Restores the terminal when dropped, even if the game panics.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Nothing sensible can be done if restoring fails.
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let puzzle = match cli.puzzle {
+        Some(line) => match Sudoku::parse_line(&line) {
+            Ok(puzzle) => puzzle,
+            Err(error) => {
+                eprintln!("sudoku-tui: invalid puzzle: {}", error);
+                return ExitCode::FAILURE;
+            },
+        },
+        None => match generate(cli.difficulty.into(), cli.seed) {
+            Some(puzzle) => puzzle,
+            None => {
+                eprintln!("sudoku-tui: no {} puzzle found, please try again", Difficulty::from(cli.difficulty));
+                return ExitCode::FAILURE;
+            },
+        },
+    };
+
+    let app = match App::new(puzzle) {
+        Ok(app) => app,
+        Err(error) => {
+            eprintln!("sudoku-tui: {}", error);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    match play(app) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("sudoku-tui: {}", error);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/// Generate a puzzle of the given [Difficulty].
+fn generate(difficulty: Difficulty, seed: Option<u64>) -> Option<Sudoku> {
+    let mut generator = match seed {
+        Some(seed) => Generator::with_seed(seed),
+        None => Generator::new(),
+    };
+
+    generator
+        .generate_with_difficulty(difficulty..=difficulty)
+        .map(|generated| generated.puzzle)
+}
+
+/// Run the game until the player quits.
+fn play(mut app: App) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut output = io::stdout().lock();
+
+    while !app.should_quit() {
+        ui::draw(&mut output, &app)?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key);
+            }
+        }
+    }
+
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use clap::CommandFactory;
+
+    use super::Cli;
+
+    /// A puzzle with a unique solution, shared by the tests of all modules.
+    ///
+    /// Values generated with http://www.opensky.ca/sudoku
+    pub(crate) const PUZZLE: &str = "..1.2.9..9...4..2..2..98.51.17......4..7.6..9......61.13.87..6..7..5...4..5.6.3..";
+
+    #[test]
+    fn cli_is_well_formed() {
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,160 @@
+//! Drawing the game onto the terminal.
+//!
+//! Every square takes up 3 x 3 characters, so the pencil marks can be shown
+//! at the same positions as on a phone keypad. A value is shown in the
+//! middle of its square.
+
+use crossterm::{cursor, queue};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{Clear, ClearType};
+
+use sudoku::NUM_SQUARES;
+
+use crate::app::{format_duration, App, Mode};
+
+use std::io::{self, Write};
+
+const KEYS: [&str; 2] = [
+    "arrows/hjkl move  1-9 enter  0 clear  p pencil marks  a fill pencil marks",
+    "u undo  r redo  b back to last correct state  ? hint  q quit",
+];
+
+/// Draw the whole screen.
+pub(crate) fn draw(output: &mut impl Write, app: &App) -> io::Result<()> {
+    // Squares with a value that appears more than once in a house
+    let mut conflicts = [false; NUM_SQUARES];
+    for conflict in app.game().grid().conflicts() {
+        for (x, y) in conflict.squares {
+            conflicts[x + y * 9] = true;
+        }
+    }
+
+    let mut line = 0;
+    draw_border(output, &mut line, '┌', '┬', '┐')?;
+
+    for y in 0..9 {
+        for row in 0..3 {
+            queue!(output, cursor::MoveTo(0, line), Print('│'))?;
+
+            for x in 0..9 {
+                queue!(output, Print(' '))?;
+                draw_square_row(output, app, x, y, row, conflicts[x + y * 9])?;
+
+                if x % 3 == 2 {
+                    queue!(output, Print(" │"))?;
+                }
+            }
+
+            line += 1;
+        }
+
+        match y {
+            2 | 5 => draw_border(output, &mut line, '├', '┼', '┤')?,
+            8 => draw_border(output, &mut line, '└', '┴', '┘')?,
+            _ => {},
+        }
+    }
+
+    let mode = match app.mode() {
+        Mode::Values => "values",
+        Mode::PencilMarks => "pencil marks",
+    };
+    queue!(
+        output,
+        cursor::MoveTo(0, line + 1),
+        Print(format!("Mode: {:<14}Time: {}", mode, format_duration(app.elapsed()))),
+        cursor::MoveTo(0, line + 2),
+        Clear(ClearType::UntilNewLine),
+    )?;
+
+    if let Some(message) = app.message() {
+        let color = if app.is_solved() { Color::Green } else { Color::Yellow };
+        queue!(output, SetForegroundColor(color), Print(message), ResetColor)?;
+    }
+
+    queue!(output, SetForegroundColor(Color::DarkGrey))?;
+    for (i, keys) in (0..).zip(KEYS) {
+        queue!(output, cursor::MoveTo(0, line + 4 + i), Print(keys))?;
+    }
+    queue!(output, ResetColor)?;
+
+    output.flush()
+}
+
+/// Draw a horizontal line between boxes.
+fn draw_border(output: &mut impl Write, line: &mut u16, left: char, middle: char, right: char) -> io::Result<()> {
+    let segment = "─".repeat(13);
+    let border = format!("{left}{segment}{middle}{segment}{middle}{segment}{right}");
+
+    queue!(output, cursor::MoveTo(0, *line), Print(border))?;
+    *line += 1;
+
+    Ok(())
+}
+
+/// Draw one of the 3 rows of characters of the square at (`x` / `y`).
+fn draw_square_row(output: &mut impl Write, app: &App, x: usize, y: usize, row: usize, conflict: bool) -> io::Result<()> {
+    let value = app.game().grid().get_value(x, y);
+
+    let text = if value != 0 {
+        if row == 1 { format!(" {} ", value) } else { "   ".to_owned() }
+    } else {
+        (1..=3)
+            .map(|column| row as u32 * 3 + column)
+            .map(|mark| if app.game().has_pencil_mark(x, y, mark) { char::from_digit(mark, 10).unwrap() } else { ' ' })
+            .collect()
+    };
+
+    let foreground = if conflict {
+        Color::Red
+    } else if value == 0 {
+        Color::DarkGrey
+    } else if app.game().is_given(x, y) {
+        Color::Reset
+    } else {
+        Color::Cyan
+    };
+
+    let background = if app.highlight().contains(&(x, y)) {
+        Color::DarkGreen
+    } else if app.cursor() == (x, y) {
+        Color::DarkBlue
+    } else {
+        Color::Reset
+    };
+
+    if app.game().is_given(x, y) {
+        queue!(output, SetAttribute(Attribute::Bold))?;
+    }
+
+    queue!(
+        output,
+        SetForegroundColor(foreground),
+        SetBackgroundColor(background),
+        Print(text),
+        SetAttribute(Attribute::Reset),
+        ResetColor,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::app::App;
+    use crate::tests::PUZZLE;
+
+    use super::draw;
+
+    #[test]
+    fn draws_grid() {
+        let app = App::new(PUZZLE.parse().unwrap()).unwrap();
+
+        let mut output = Vec::new();
+        draw(&mut output, &app).unwrap();
+        let screen = String::from_utf8(output).unwrap();
+
+        assert!(screen.contains('┼'));
+        assert!(screen.contains(" 9 "));
+        assert!(screen.contains("Mode: values"));
+    }
+}
@@ -0,0 +1,387 @@
+//! A backtracking solver keeping the used values of every house in bitmasks.
+//!
+//! Instead of recomputing the candidates of the whole grid after every
+//! placement, the masks are updated incrementally when a value is placed and
+//! updated back when the search backtracks.
+
+use crate::{Sudoku, NUM_SQUARES};
+use crate::solver::{Branch, Branching};
+
+use serde::{Deserialize, Serialize};
+
+/// A bitmask with one bit for each of the values 1 to 9.
+const ALL_VALUES: u16 = 0x1ff;
+
+/// The [Iterator] behind [crate::solver::Solutions] for
+/// [crate::solver::Engine::Bitboard].
+///
+/// `rows`, `columns` and `boxes` contain a bit for every value used within the
+/// house, bit `value - 1` for `value`.
+///
+/// The search runs iteratively, `stack` contains one [Guess] for every square
+/// that was filled in by the solver, so the search can be continued after a
+/// solution was returned.
+#[derive(Clone)]
+pub(crate) struct Bitboard {
+    grid: [u8; NUM_SQUARES],
+    rows: [u16; 9],
+    columns: [u16; 9],
+    boxes: [u16; 9],
+    branching: Branching,
+    stack: Vec<Guess>,
+    /// Whether the search should continue with the next alternative of the
+    /// [Guess] on top of `stack` instead of going deeper.
+    backtracking: bool,
+    finished: bool,
+}
+
+/// A square filled in by [Bitboard].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Guess {
+    square: usize,
+    /// The alternatives not tried yet.
+    remaining: Branch,
+}
+
+impl Bitboard {
+
+    /// Initialize a new [Bitboard] with the clues of `sudoku`.
+    pub(crate) fn new(sudoku: &Sudoku, branching: Branching) -> Bitboard {
+        let mut bitboard = Bitboard {
+            grid: [0; NUM_SQUARES],
+            rows: [0; 9],
+            columns: [0; 9],
+            boxes: [0; 9],
+            branching,
+            stack: Vec::with_capacity(sudoku.num_empty_squares()),
+            backtracking: false,
+            // Duplicate values cannot be stored in the masks.
+            finished: !sudoku.is_valid(),
+        };
+
+        for (square, &value) in sudoku.grid.iter().enumerate() {
+            if value != 0 {
+                bitboard.place(square, value as u8);
+            }
+        }
+
+        bitboard
+    }
+
+    /// Get the state of the search, see [crate::solver::Solutions::state()].
+    pub(crate) fn state(&self) -> BitboardState {
+        BitboardState {
+            grid: self.sudoku(),
+            branching: self.branching,
+            stack: self.stack.clone(),
+            backtracking: self.backtracking,
+            finished: self.finished,
+        }
+    }
+
+    /// Continue a search for the solutions of `sudoku` from a saved `state`.
+    ///
+    /// Return an error if `state` cannot belong to a search for `sudoku`.
+    pub(crate) fn resume(sudoku: &Sudoku, state: BitboardState) -> Result<Bitboard, &'static str> {
+        if state.finished {
+            return Ok(Bitboard {
+                finished: true,
+                ..Bitboard::new(sudoku, state.branching)
+            });
+        }
+
+        if !state.grid.is_valid() {
+            return Err("grid contains duplicate values");
+        }
+
+        let keeps_clues = (0..NUM_SQUARES).all(|square| sudoku.grid[square] == 0 || sudoku.grid[square] == state.grid.grid[square]);
+        if !keeps_clues {
+            return Err("grid does not contain the clues of the puzzle");
+        }
+
+        // Every square filled in by the search must be on the stack exactly
+        // once, and the alternatives must be in bounds. Replaying the guesses
+        // checks that the alternatives left could be placed when the guess
+        // was made, so backtracking never places a value onto a clue or an
+        // earlier guess.
+        let mut guessed = [false; NUM_SQUARES];
+        let mut replay = Bitboard::new(sudoku, state.branching);
+        for guess in &state.stack {
+            if guess.square >= NUM_SQUARES || guessed[guess.square] || !guess.remaining.is_in_bounds() {
+                return Err("invalid guess");
+            }
+            if !replay.allows(guess.square, &guess.remaining) {
+                return Err("invalid guess");
+            }
+            guessed[guess.square] = true;
+
+            let value = state.grid.grid[guess.square];
+            if value != 0 && replay.grid[guess.square] == 0 {
+                replay.place(guess.square, value as u8);
+            }
+        }
+
+        let all_guessed = (0..NUM_SQUARES).all(|square| {
+            guessed[square] == (sudoku.grid[square] == 0 && state.grid.grid[square] != 0)
+        });
+        if !all_guessed {
+            return Err("stack does not match the grid");
+        }
+
+        Ok(Bitboard {
+            stack: state.stack,
+            backtracking: state.backtracking,
+            ..Bitboard::new(&state.grid, state.branching)
+        })
+    }
+
+    /// Get the values that can still be placed on `square`.
+    fn candidates(&self, square: usize) -> u16 {
+        let (x, y) = (square % 9, square / 9);
+
+        !(self.rows[y] | self.columns[x] | self.boxes[box_index(x, y)]) & ALL_VALUES
+    }
+
+    /// Check if every alternative of `branch` can be placed, where `square`
+    /// is the square of the alternative that was tried.
+    fn allows(&self, square: usize, branch: &Branch) -> bool {
+        match *branch {
+            Branch::Square { square: branch_square, values } => {
+                branch_square == square && self.grid[square] == 0 && values & !self.candidates(square) == 0
+            },
+            Branch::House { house, value, squares } => house
+                .squares()
+                .into_iter()
+                .enumerate()
+                .filter(|&(i, _)| squares >> i & 1 == 1)
+                .all(|(_, (x, y))| self.grid[x + y * 9] == 0 && self.candidates(x + y * 9) >> (value - 1) & 1 == 1),
+        }
+    }
+
+    /// Place `value` on the empty `square`.
+    fn place(&mut self, square: usize, value: u8) {
+        let (x, y) = (square % 9, square / 9);
+        let flag = 1 << (value - 1);
+
+        self.grid[square] = value;
+        self.rows[y] |= flag;
+        self.columns[x] |= flag;
+        self.boxes[box_index(x, y)] |= flag;
+    }
+
+    /// Undo [Bitboard::place()].
+    fn remove(&mut self, square: usize) {
+        let (x, y) = (square % 9, square / 9);
+        let flag = !(1 << (self.grid[square] - 1));
+
+        self.grid[square] = 0;
+        self.rows[y] &= flag;
+        self.columns[x] &= flag;
+        self.boxes[box_index(x, y)] &= flag;
+    }
+
+    /// Choose where to branch next, see [Branching::choose()].
+    fn choose_branch(&self) -> Option<Branch> {
+        let candidates = std::array::from_fn(|square| {
+            if self.grid[square] == 0 {
+                self.candidates(square)
+            } else {
+                0
+            }
+        });
+
+        self.branching.choose(&candidates, |square| u32::from(self.grid[square]))
+    }
+
+    /// Take the next alternative out of `branch` and place it.
+    ///
+    /// Return the square the value was placed on, or `None` if there are no
+    /// alternatives left.
+    fn place_next(&mut self, branch: &mut Branch) -> Option<usize> {
+        match branch {
+            Branch::Square { square, values } => {
+                if *values == 0 {
+                    return None;
+                }
+
+                let value = lowest_value(*values);
+                *values &= *values - 1;
+                self.place(*square, value);
+
+                Some(*square)
+            },
+            Branch::House { house, value, squares } => {
+                if *squares == 0 {
+                    return None;
+                }
+
+                let (x, y) = house.squares()[squares.trailing_zeros() as usize];
+                *squares &= *squares - 1;
+                self.place(x + y * 9, *value as u8);
+
+                Some(x + y * 9)
+            },
+        }
+    }
+
+    /// Turn the grid into a [Sudoku].
+    fn sudoku(&self) -> Sudoku {
+        Sudoku {
+            grid: self.grid.map(u32::from),
+        }
+    }
+}
+
+impl Iterator for Bitboard {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Sudoku> {
+
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            if self.backtracking {
+                let mut guess = match self.stack.pop() {
+                    Some(guess) => guess,
+                    None => {
+                        self.finished = true;
+                        return None;
+                    },
+                };
+
+                // try the next alternative of the same branch
+                self.remove(guess.square);
+                if let Some(square) = self.place_next(&mut guess.remaining) {
+                    guess.square = square;
+                    self.stack.push(guess);
+                    self.backtracking = false;
+                }
+                continue;
+            }
+
+            let mut branch = match self.choose_branch() {
+                Some(branch) => branch,
+                None => {
+                    self.backtracking = true;
+                    return Some(self.sudoku());
+                },
+            };
+
+            match self.place_next(&mut branch) {
+                Some(square) => self.stack.push(Guess {
+                    square,
+                    remaining: branch,
+                }),
+                None => self.backtracking = true,
+            }
+        }
+    }
+}
+
+/// The saved state of a [Bitboard].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BitboardState {
+    grid: Sudoku,
+    branching: Branching,
+    stack: Vec<Guess>,
+    backtracking: bool,
+    finished: bool,
+}
+
+/// Get the index of the box containing the square at (`x` / `y`).
+fn box_index(x: usize, y: usize) -> usize {
+    x / 3 + (y / 3) * 3
+}
+
+/// Get the smallest value within the non-empty bitmask `flags`.
+fn lowest_value(flags: u16) -> u8 {
+    flags.trailing_zeros() as u8 + 1
+}
+
+#[cfg(test)]
+mod tests {
+
+    use itertools::Itertools;
+
+    use crate::{House, Sudoku};
+    use crate::solver::{Branch, Branching};
+    use crate::tests::PUZZLE;
+
+    use super::{Bitboard, BitboardState, Guess};
+
+    #[test]
+    fn finds_unique_solution() {
+        let sudoku: Sudoku = PUZZLE.parse().unwrap();
+
+        let solutions = Bitboard::new(&sudoku, Branching::default()).collect::<Vec<_>>();
+
+        assert_eq!(solutions.len(), 1);
+        assert!(solutions[0].is_solved());
+    }
+
+    #[test]
+    fn resume_rejects_guesses_onto_clues() {
+        let sudoku: Sudoku = PUZZLE.parse().unwrap();
+        let mut grid = sudoku;
+        grid.set_value(0, 0, 5);
+
+        let state = |remaining| BitboardState {
+            grid,
+            branching: Branching::FewestOptions,
+            stack: vec![Guess { square: 0, remaining }],
+            backtracking: true,
+            finished: false,
+        };
+
+        // 1 is a clue at (2 / 0)
+        let onto_clue = Branch::House { house: House::Row(0), value: 5, squares: 0b100 };
+        assert!(Bitboard::resume(&sudoku, state(onto_clue)).is_err());
+
+        let impossible_value = Branch::Square { square: 0, values: 0b1 };
+        assert!(Bitboard::resume(&sudoku, state(impossible_value)).is_err());
+
+        // 8 is a candidate of (0 / 0)
+        let other_value = Branch::Square { square: 0, values: 0b1000_0000 };
+        let resumed = Bitboard::resume(&sudoku, state(other_value)).unwrap();
+        assert_eq!(resumed.count(), 0);
+    }
+
+    #[test]
+    fn finds_all_solutions() {
+        // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+        let sudoku: Sudoku = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4".parse().unwrap();
+
+        let solutions = Bitboard::new(&sudoku, Branching::default()).collect::<Vec<_>>();
+
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all_unique());
+        assert!(solutions.iter().all(|solution| solution.is_solved()));
+    }
+
+    #[test]
+    fn solved_and_invalid_grids() {
+        let solved: Sudoku = "726853419159274863483619275618925734942367158375481692294736581537148926861592347".parse().unwrap();
+        assert_eq!(Bitboard::new(&solved, Branching::default()).collect::<Vec<_>>(), vec![solved]);
+
+        let invalid: Sudoku = ("11".to_owned() + &".".repeat(79)).parse().unwrap();
+        let mut solutions = Bitboard::new(&invalid, Branching::default());
+        assert_eq!(solutions.next(), None);
+        assert_eq!(solutions.next(), None);
+
+        let dead_end: Sudoku = ("12.456789..3".to_owned() + &".".repeat(69)).parse().unwrap();
+        assert_eq!(Bitboard::new(&dead_end, Branching::default()).next(), None);
+    }
+
+    #[test]
+    fn masks_are_restored() {
+        let sudoku: Sudoku = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4".parse().unwrap();
+
+        let mut solutions = Bitboard::new(&sudoku, Branching::default());
+        let initial = (solutions.rows, solutions.columns, solutions.boxes);
+
+        assert_eq!(solutions.by_ref().count(), 3);
+        assert_eq!((solutions.rows, solutions.columns, solutions.boxes), initial);
+    }
+}
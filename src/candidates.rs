@@ -0,0 +1,491 @@
+//! Sets of values that can still be placed into a square.
+//!
+//! [Sudoku::candidates()] and [Sudoku::candidates_at()] compute the
+//! [Candidates] of the squares of a [Sudoku]: every value that does not
+//! appear in the row, the column or the 3x3 cell of the square yet.
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::candidates::Candidates;
+//!
+//! let mut sudoku = Sudoku::new_empty();
+//! sudoku.set_value(0, 0, 1);
+//! sudoku.set_value(1, 0, 2);
+//! sudoku.set_value(3, 1, 3);
+//! sudoku.set_value(4, 4, 4);
+//!
+//! // 1 and 2 are in the same row, 3 in the same box and 4 in the same column
+//! let candidates = sudoku.candidates_at(4, 0);
+//! assert_eq!(candidates, Candidates::from_iter([5, 6, 7, 8, 9]));
+//!
+//! // The values that can go into (8 / 0), but not into (4 / 0)
+//! let difference = sudoku.candidates_at(8, 0) - candidates;
+//! assert_eq!(difference.iter().collect::<Vec<_>>(), vec![3, 4]);
+//! ```
+//!
+//! [Sudoku]: crate::Sudoku
+//! [Sudoku::candidates()]: crate::Sudoku::candidates()
+//! [Sudoku::candidates_at()]: crate::Sudoku::candidates_at()
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Sub, SubAssign};
+
+/// The flags of all values from 1 to 9.
+const ALL_FLAGS: u16 = 0b111_111_111;
+
+/// A set of the values 1 to 9, e.g. the values that can still be placed into
+/// a square.
+///
+/// The set operations are available as methods and as operators: `|` is the
+/// union, `&` the intersection, `-` the difference, `^` the symmetric
+/// difference and `!` the complement.
+///
+/// ```
+/// use sudoku::candidates::Candidates;
+///
+/// let a = Candidates::from_iter([1, 2, 3]);
+/// let b = Candidates::from_iter([3, 4]);
+///
+/// assert_eq!(a | b, Candidates::from_iter([1, 2, 3, 4]));
+/// assert_eq!(a & b, Candidates::from_iter([3]));
+/// assert_eq!(a - b, Candidates::from_iter([1, 2]));
+/// assert_eq!(!a, Candidates::from_iter(4..=9));
+/// ```
+///
+/// [Candidates] are formatted as their values in ascending order, e.g. `"149"`,
+/// and serialized as a sequence of their values.
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct Candidates {
+    /// Bit `value - 1` is set if `value` is in the set.
+    flags: u16,
+}
+
+impl Candidates {
+
+    /// Get the empty set.
+    pub const fn new() -> Candidates {
+        Candidates { flags: 0 }
+    }
+
+    /// Get the set of all values from 1 to 9.
+    pub const fn all() -> Candidates {
+        Candidates { flags: ALL_FLAGS }
+    }
+
+    /// Get the set whose bit `value - 1` is set for every `value` in it.
+    ///
+    /// Return `None` if any bit above the ninth is set.
+    ///
+    /// ```
+    /// use sudoku::candidates::Candidates;
+    ///
+    /// assert_eq!(Candidates::from_bits(0b100_000_101), Some(Candidates::from_iter([1, 3, 9])));
+    /// assert_eq!(Candidates::from_bits(0b1_000_000_000), None);
+    /// ```
+    pub const fn from_bits(bits: u16) -> Option<Candidates> {
+        if bits & !ALL_FLAGS != 0 {
+            return None;
+        }
+
+        Some(Candidates { flags: bits })
+    }
+
+    /// Get the bits of this set, bit `value - 1` is set for every `value` in
+    /// it. See [Candidates::from_bits()].
+    pub const fn bits(&self) -> u16 {
+        self.flags
+    }
+
+    /// Check if `value` is in this set.
+    ///
+    /// Panics if `value` is not in the range 1 to 9.
+    pub fn contains(&self, value: u32) -> bool {
+        self.flags & flag(value) != 0
+    }
+
+    /// Add `value` to this set.
+    ///
+    /// Return `true` if `value` was not in the set before.
+    ///
+    /// Panics if `value` is not in the range 1 to 9.
+    pub fn insert(&mut self, value: u32) -> bool {
+        let added = !self.contains(value);
+        self.flags |= flag(value);
+
+        added
+    }
+
+    /// Remove `value` from this set.
+    ///
+    /// Return `true` if `value` was in the set before.
+    ///
+    /// Panics if `value` is not in the range 1 to 9.
+    pub fn remove(&mut self, value: u32) -> bool {
+        let removed = self.contains(value);
+        self.flags &= !flag(value);
+
+        removed
+    }
+
+    /// Add `value` to this set if it is missing, remove it otherwise.
+    ///
+    /// Return `true` if `value` is in the set afterwards.
+    ///
+    /// Panics if `value` is not in the range 1 to 9.
+    pub fn toggle(&mut self, value: u32) -> bool {
+        self.flags ^= flag(value);
+
+        self.contains(value)
+    }
+
+    /// Get the number of values in this set.
+    pub fn len(&self) -> usize {
+        self.flags.count_ones() as usize
+    }
+
+    /// Check if this set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.flags == 0
+    }
+
+    /// Get the only value in this set, or `None` if it contains no or more
+    /// than one value.
+    ///
+    /// A square with a single candidate is a naked single.
+    pub fn single(&self) -> Option<u32> {
+        if self.len() != 1 {
+            return None;
+        }
+
+        Some(self.flags.trailing_zeros() + 1)
+    }
+
+    /// Get the values that are in this set, in `other` or in both.
+    pub fn union(&self, other: Candidates) -> Candidates {
+        *self | other
+    }
+
+    /// Get the values that are in both this set and `other`.
+    pub fn intersection(&self, other: Candidates) -> Candidates {
+        *self & other
+    }
+
+    /// Get the values that are in this set but not in `other`.
+    pub fn difference(&self, other: Candidates) -> Candidates {
+        *self - other
+    }
+
+    /// Get the values that are in either this set or `other`, but not in
+    /// both.
+    pub fn symmetric_difference(&self, other: Candidates) -> Candidates {
+        *self ^ other
+    }
+
+    /// Get the values from 1 to 9 that are not in this set.
+    pub fn complement(&self) -> Candidates {
+        !*self
+    }
+
+    /// Check if every value in this set is also in `other`.
+    pub fn is_subset(&self, other: Candidates) -> bool {
+        self.flags & !other.flags == 0
+    }
+
+    /// Check if every value in `other` is also in this set.
+    pub fn is_superset(&self, other: Candidates) -> bool {
+        other.is_subset(*self)
+    }
+
+    /// Check if this set and `other` have no values in common.
+    pub fn is_disjoint(&self, other: Candidates) -> bool {
+        self.flags & other.flags == 0
+    }
+
+    /// Get an [Iterator] over the values in this set, in ascending order.
+    pub fn iter(&self) -> Iter {
+        Iter {
+            flags: self.flags,
+        }
+    }
+}
+
+/// Get the flag of `value`, panicking if it is not in the range 1 to 9.
+fn flag(value: u32) -> u16 {
+    if !(1..=9).contains(&value) {
+        panic!("Candidate values must be in the range 1 to 9 (was {})", value);
+    }
+
+    1 << (value - 1)
+}
+
+impl BitOr for Candidates {
+    type Output = Candidates;
+
+    fn bitor(self, other: Candidates) -> Candidates {
+        Candidates { flags: self.flags | other.flags }
+    }
+}
+
+impl BitOrAssign for Candidates {
+    fn bitor_assign(&mut self, other: Candidates) {
+        self.flags |= other.flags;
+    }
+}
+
+impl BitAnd for Candidates {
+    type Output = Candidates;
+
+    fn bitand(self, other: Candidates) -> Candidates {
+        Candidates { flags: self.flags & other.flags }
+    }
+}
+
+impl BitAndAssign for Candidates {
+    fn bitand_assign(&mut self, other: Candidates) {
+        self.flags &= other.flags;
+    }
+}
+
+impl BitXor for Candidates {
+    type Output = Candidates;
+
+    fn bitxor(self, other: Candidates) -> Candidates {
+        Candidates { flags: self.flags ^ other.flags }
+    }
+}
+
+impl BitXorAssign for Candidates {
+    fn bitxor_assign(&mut self, other: Candidates) {
+        self.flags ^= other.flags;
+    }
+}
+
+impl Sub for Candidates {
+    type Output = Candidates;
+
+    fn sub(self, other: Candidates) -> Candidates {
+        Candidates { flags: self.flags & !other.flags }
+    }
+}
+
+impl SubAssign for Candidates {
+    fn sub_assign(&mut self, other: Candidates) {
+        self.flags &= !other.flags;
+    }
+}
+
+impl Not for Candidates {
+    type Output = Candidates;
+
+    fn not(self) -> Candidates {
+        Candidates { flags: !self.flags & ALL_FLAGS }
+    }
+}
+
+/// Panics if a value is not in the range 1 to 9.
+impl FromIterator<u32> for Candidates {
+    fn from_iter<I: IntoIterator<Item = u32>>(values: I) -> Candidates {
+        let mut candidates = Candidates::new();
+        candidates.extend(values);
+
+        candidates
+    }
+}
+
+/// Panics if a value is not in the range 1 to 9.
+impl Extend<u32> for Candidates {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+impl IntoIterator for Candidates {
+    type Item = u32;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &Candidates {
+    type Item = u32;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for Candidates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Write the values in ascending order without separators, e.g. `149`.
+impl fmt::Display for Candidates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|value| write!(f, "{}", value))
+    }
+}
+
+/// [Candidates] are serialized as a sequence of their values in ascending
+/// order.
+impl Serialize for Candidates {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// [Candidates] are deserialized from a sequence of values from 1 to 9.
+impl<'de> Deserialize<'de> for Candidates {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Candidates, D::Error> {
+        let values = Vec::<u32>::deserialize(deserializer)?;
+
+        if let Some(value) = values.iter().find(|value| !(1..=9).contains(*value)) {
+            return Err(serde::de::Error::custom(format_args!("invalid candidate {}, expected 1 to 9", value)));
+        }
+
+        Ok(values.into_iter().collect())
+    }
+}
+
+/// The [Iterator] returned by [Candidates::iter()].
+#[derive(Clone, Debug)]
+pub struct Iter {
+    /// The flags of the values that have not been returned yet.
+    flags: u16,
+}
+
+impl Iterator for Iter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.flags == 0 {
+            return None;
+        }
+
+        let value = self.flags.trailing_zeros() + 1;
+        // Clear the lowest set flag
+        self.flags &= self.flags - 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.flags.count_ones() as usize;
+
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Iter {
+    fn next_back(&mut self) -> Option<u32> {
+        if self.flags == 0 {
+            return None;
+        }
+
+        let value = 16 - self.flags.leading_zeros();
+        self.flags &= !(1 << (value - 1));
+
+        Some(value)
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+impl FusedIterator for Iter {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Candidates;
+
+    #[test]
+    fn insert_remove_toggle() {
+        let mut candidates = Candidates::new();
+        assert!(candidates.is_empty());
+
+        assert!(candidates.insert(4));
+        assert!(!candidates.insert(4));
+        assert!(candidates.contains(4));
+        assert_eq!(candidates.single(), Some(4));
+
+        assert!(candidates.toggle(9));
+        assert!(!candidates.toggle(4));
+        assert_eq!(candidates.single(), Some(9));
+
+        assert!(candidates.remove(9));
+        assert!(!candidates.remove(9));
+        assert!(candidates.is_empty());
+        assert_eq!(candidates.single(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn contains_panics_on_invalid_value() {
+        Candidates::all().contains(10);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = Candidates::from_iter([1, 2, 3, 5]);
+        let b = Candidates::from_iter([2, 5, 8]);
+
+        assert_eq!(a.union(b), Candidates::from_iter([1, 2, 3, 5, 8]));
+        assert_eq!(a.intersection(b), Candidates::from_iter([2, 5]));
+        assert_eq!(a.difference(b), Candidates::from_iter([1, 3]));
+        assert_eq!(a.symmetric_difference(b), Candidates::from_iter([1, 3, 8]));
+        assert_eq!(a.complement(), Candidates::from_iter([4, 6, 7, 8, 9]));
+        assert_eq!(Candidates::all().complement(), Candidates::new());
+
+        assert!(Candidates::from_iter([2, 5]).is_subset(a));
+        assert!(a.is_superset(Candidates::from_iter([2, 5])));
+        assert!(!a.is_subset(b));
+        assert!(a.is_disjoint(Candidates::from_iter([4, 9])));
+
+        let mut c = a;
+        c -= b;
+        c |= Candidates::from_iter([9]);
+        c &= Candidates::from_iter([1, 9]);
+        c ^= Candidates::from_iter([1, 7]);
+        assert_eq!(c, Candidates::from_iter([7, 9]));
+    }
+
+    #[test]
+    fn iteration() {
+        let candidates = Candidates::from_iter([9, 1, 6, 3]);
+
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), vec![1, 3, 6, 9]);
+        assert_eq!(candidates.iter().rev().collect::<Vec<_>>(), vec![9, 6, 3, 1]);
+        assert_eq!(candidates.iter().len(), 4);
+        assert_eq!(Candidates::all().into_iter().sum::<u32>(), 45);
+    }
+
+    #[test]
+    fn formatting() {
+        let candidates = Candidates::from_iter([1, 4, 9]);
+
+        assert_eq!(candidates.to_string(), "149");
+        assert_eq!(format!("{:?}", candidates), "{1, 4, 9}");
+        assert_eq!(Candidates::new().to_string(), "");
+    }
+
+    #[test]
+    fn serde() {
+        let candidates = Candidates::from_iter([2, 7]);
+
+        let json = serde_json::to_string(&candidates).unwrap();
+        assert_eq!(json, "[2,7]");
+        assert_eq!(serde_json::from_str::<Candidates>(&json).unwrap(), candidates);
+
+        assert!(serde_json::from_str::<Candidates>("[0]").is_err());
+        assert!(serde_json::from_str::<Candidates>("[10]").is_err());
+    }
+}
@@ -0,0 +1,390 @@
+//! An exact cover solver based on Donald Knuth's Dancing Links (Algorithm X).
+//!
+//! A Sudoku is an exact cover problem with 324 constraints (columns): every
+//! square holds a value, and every row, column and box contains every value
+//! exactly once. Each of the 729 possible placements (a value in a square)
+//! satisfies exactly 4 of those constraints.
+
+use crate::{Sudoku, NUM_SQUARES};
+
+use serde::{Deserialize, Serialize};
+
+/// The number of constraints of a Sudoku.
+const NUM_COLUMNS: usize = 4 * NUM_SQUARES;
+
+/// The number of possible placements of a Sudoku.
+const NUM_ROWS: usize = 9 * NUM_SQUARES;
+
+/// Index of the root node, the column headers follow right after it.
+const ROOT: usize = 0;
+
+/// The [Iterator] behind [crate::solver::Solutions] for
+/// [crate::solver::Engine::DancingLinks].
+///
+/// All links are stored as indices into the node vectors. Nodes `1..=324` are
+/// the column headers, the 4 nodes of each placement follow after them.
+///
+/// The search runs iteratively, `stack` contains the node of each placement
+/// that was chosen, so the search can be continued after a solution was
+/// returned.
+#[derive(Clone)]
+pub(crate) struct DancingLinks {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    /// The placement each node belongs to, as `square * 9 + value - 1`.
+    row: Vec<usize>,
+    /// The number of nodes in each column (only used for column headers).
+    size: Vec<usize>,
+    grid: Sudoku,
+    stack: Vec<usize>,
+    /// Whether the search should continue with the next placement for the
+    /// node on top of `stack` instead of going deeper.
+    backtracking: bool,
+    finished: bool,
+}
+
+impl DancingLinks {
+
+    /// Build the exact cover matrix for `sudoku` and remove the constraints
+    /// satisfied by its clues.
+    pub(crate) fn new(sudoku: &Sudoku) -> DancingLinks {
+        let num_nodes = 1 + NUM_COLUMNS + 4 * NUM_ROWS;
+
+        let mut dlx = DancingLinks {
+            left: Vec::with_capacity(num_nodes),
+            right: Vec::with_capacity(num_nodes),
+            up: Vec::with_capacity(num_nodes),
+            down: Vec::with_capacity(num_nodes),
+            column: Vec::with_capacity(num_nodes),
+            row: Vec::with_capacity(num_nodes),
+            size: vec![0; 1 + NUM_COLUMNS],
+            grid: *sudoku,
+            stack: Vec::with_capacity(NUM_SQUARES),
+            backtracking: false,
+            // A grid with duplicate values has no solution, but the matrix
+            // cannot express that.
+            finished: !sudoku.is_valid(),
+        };
+
+        // root and column headers, linked in a circle
+        for node in 0..=NUM_COLUMNS {
+            dlx.left.push(if node == 0 { NUM_COLUMNS } else { node - 1 });
+            dlx.right.push(if node == NUM_COLUMNS { 0 } else { node + 1 });
+            dlx.up.push(node);
+            dlx.down.push(node);
+            dlx.column.push(node);
+            dlx.row.push(usize::MAX);
+        }
+
+        let mut first_nodes = [0; NUM_ROWS];
+
+        for (placement, first_node) in first_nodes.iter_mut().enumerate() {
+            let first = dlx.left.len();
+            *first_node = first;
+
+            for (i, &header) in constraints(placement).iter().enumerate() {
+                let node = first + i;
+
+                dlx.left.push(if i == 0 { first + 3 } else { node - 1 });
+                dlx.right.push(if i == 3 { first } else { node + 1 });
+
+                // append at the bottom of the column
+                let last = dlx.up[header];
+                dlx.up.push(last);
+                dlx.down.push(header);
+                dlx.down[last] = node;
+                dlx.up[header] = node;
+
+                dlx.column.push(header);
+                dlx.row.push(placement);
+                dlx.size[header] += 1;
+            }
+        }
+
+        if !dlx.finished {
+            for (square, &value) in sudoku.grid.iter().enumerate() {
+                if value != 0 {
+                    let node = first_nodes[square * 9 + value as usize - 1];
+                    dlx.cover(dlx.column[node]);
+                    dlx.cover_others(node);
+                }
+            }
+        }
+
+        dlx
+    }
+
+    /// Get the state of the search, see [crate::solver::Solutions::state()].
+    pub(crate) fn state(&self) -> DancingLinksState {
+        DancingLinksState {
+            stack: self.stack.clone(),
+            backtracking: self.backtracking,
+            finished: self.finished,
+        }
+    }
+
+    /// Continue a search for the solutions of `sudoku` from a saved `state`.
+    ///
+    /// The nodes on the stack are chosen again in the same order, which
+    /// brings the links into exactly the same state as before.
+    ///
+    /// Return an error if `state` cannot belong to a search for `sudoku`.
+    pub(crate) fn resume(sudoku: &Sudoku, state: DancingLinksState) -> Result<DancingLinks, &'static str> {
+        let mut dlx = DancingLinks::new(sudoku);
+
+        if state.finished || dlx.finished {
+            dlx.finished = true;
+            return Ok(dlx);
+        }
+
+        for &node in &state.stack {
+            if node <= NUM_COLUMNS || node >= dlx.left.len() || !dlx.is_available(node) {
+                return Err("invalid node on the stack");
+            }
+
+            dlx.cover(dlx.column[node]);
+            dlx.cover_others(node);
+            dlx.stack.push(node);
+        }
+
+        dlx.backtracking = state.backtracking;
+
+        Ok(dlx)
+    }
+
+    /// Check if `node` is still linked into its column and its column is
+    /// not covered.
+    fn is_available(&self, node: usize) -> bool {
+        let header = self.column[node];
+
+        let mut column_header = self.right[ROOT];
+        while column_header != header {
+            if column_header == ROOT {
+                return false;
+            }
+            column_header = self.right[column_header];
+        }
+
+        let mut row_node = self.down[header];
+        while row_node != header {
+            if row_node == node {
+                return true;
+            }
+            row_node = self.down[row_node];
+        }
+
+        false
+    }
+
+    /// Remove column `header` from the header list and all placements
+    /// satisfying it from the other columns.
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+
+        let mut row_node = self.down[header];
+        while row_node != header {
+            let mut node = self.right[row_node];
+            while node != row_node {
+                let (up, down) = (self.up[node], self.down[node]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row_node = self.down[row_node];
+        }
+    }
+
+    /// Undo [DancingLinks::cover()], in exactly the reverse order.
+    fn uncover(&mut self, header: usize) {
+        let mut row_node = self.up[header];
+        while row_node != header {
+            let mut node = self.left[row_node];
+            while node != row_node {
+                let (up, down) = (self.up[node], self.down[node]);
+                self.down[up] = node;
+                self.up[down] = node;
+                self.size[self.column[node]] += 1;
+                node = self.left[node];
+            }
+            row_node = self.up[row_node];
+        }
+
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+    }
+
+    /// Cover the columns of all nodes in the row of `row_node` except for its
+    /// own.
+    fn cover_others(&mut self, row_node: usize) {
+        let mut node = self.right[row_node];
+        while node != row_node {
+            self.cover(self.column[node]);
+            node = self.right[node];
+        }
+    }
+
+    /// Undo [DancingLinks::cover_others()].
+    fn uncover_others(&mut self, row_node: usize) {
+        let mut node = self.left[row_node];
+        while node != row_node {
+            self.uncover(self.column[node]);
+            node = self.left[node];
+        }
+    }
+
+    /// Get the uncovered column with the fewest nodes.
+    fn smallest_column(&self) -> usize {
+        let mut smallest = self.right[ROOT];
+        let mut header = self.right[smallest];
+
+        while header != ROOT && self.size[smallest] > 0 {
+            if self.size[header] < self.size[smallest] {
+                smallest = header;
+            }
+            header = self.right[header];
+        }
+
+        smallest
+    }
+
+    /// Turn the placements on `stack` into a solved grid.
+    fn solution(&self) -> Sudoku {
+        let mut solution = self.grid;
+
+        for &node in &self.stack {
+            let placement = self.row[node];
+            solution.grid[placement / 9] = (placement % 9) as u32 + 1;
+        }
+
+        solution
+    }
+}
+
+impl Iterator for DancingLinks {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Sudoku> {
+
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            if self.backtracking {
+                // Try the next placement for the column of the last choice,
+                // or give up on that column and keep backtracking.
+                let node = match self.stack.pop() {
+                    Some(node) => node,
+                    None => {
+                        self.finished = true;
+                        return None;
+                    },
+                };
+                self.uncover_others(node);
+
+                let header = self.column[node];
+                let next = self.down[node];
+                if next == header {
+                    self.uncover(header);
+                    continue;
+                }
+
+                self.cover_others(next);
+                self.stack.push(next);
+                self.backtracking = false;
+            }
+
+            // all constraints are satisfied
+            if self.right[ROOT] == ROOT {
+                self.backtracking = true;
+                return Some(self.solution());
+            }
+
+            let header = self.smallest_column();
+            if self.size[header] == 0 {
+                self.backtracking = true;
+                continue;
+            }
+
+            self.cover(header);
+            let node = self.down[header];
+            self.cover_others(node);
+            self.stack.push(node);
+        }
+    }
+}
+
+/// The saved state of [DancingLinks].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DancingLinksState {
+    stack: Vec<usize>,
+    backtracking: bool,
+    finished: bool,
+}
+
+/// Get the column headers of the 4 constraints satisfied by `placement`.
+fn constraints(placement: usize) -> [usize; 4] {
+    let (square, value) = (placement / 9, placement % 9);
+    let (x, y) = (square % 9, square / 9);
+    let box_index = x / 3 + (y / 3) * 3;
+
+    [
+        1 + square,
+        1 + NUM_SQUARES + y * 9 + value,
+        1 + 2 * NUM_SQUARES + x * 9 + value,
+        1 + 3 * NUM_SQUARES + box_index * 9 + value,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+
+    use itertools::Itertools;
+
+    use crate::Sudoku;
+    use crate::tests::PUZZLE;
+
+    use super::DancingLinks;
+
+    #[test]
+    fn finds_unique_solution() {
+        let sudoku: Sudoku = PUZZLE.parse().unwrap();
+
+        let solutions = DancingLinks::new(&sudoku).collect::<Vec<_>>();
+
+        assert_eq!(solutions, vec![sudoku.find_solution().unwrap()]);
+    }
+
+    #[test]
+    fn finds_all_solutions() {
+        // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+        let sudoku: Sudoku = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4".parse().unwrap();
+
+        let solutions = DancingLinks::new(&sudoku).sorted_by_key(|solution| solution.grid).collect::<Vec<_>>();
+        let expected = sudoku.find_all_solutions().sorted_by_key(|solution| solution.grid).collect::<Vec<_>>();
+
+        assert_eq!(solutions.len(), 3);
+        assert_eq!(solutions, expected);
+    }
+
+    #[test]
+    fn solved_and_invalid_grids() {
+        let solved: Sudoku = "726853419159274863483619275618925734942367158375481692294736581537148926861592347".parse().unwrap();
+        assert_eq!(DancingLinks::new(&solved).collect::<Vec<_>>(), vec![solved]);
+
+        let invalid: Sudoku = ("11".to_owned() + &".".repeat(79)).parse().unwrap();
+        let mut solutions = DancingLinks::new(&invalid);
+        assert_eq!(solutions.next(), None);
+        assert_eq!(solutions.next(), None);
+
+        let dead_end: Sudoku = ("12.456789..3".to_owned() + &".".repeat(69)).parse().unwrap();
+        assert_eq!(DancingLinks::new(&dead_end).next(), None);
+    }
+}
@@ -0,0 +1,378 @@
+//! Explaining the steps of the [LogicalSolver] in words.
+//!
+//! A [StepFormatter] turns a [SolveStep] into a sentence. [English] is the
+//! formatter used by default, other languages can be supported by
+//! implementing [StepFormatter]. Squares are named in the usual `r4c7`
+//! notation (see [cell_name()]), rows, columns and boxes are numbered from 1
+//! to 9.
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::explain::{English, StepFormatter};
+//!
+//! // Values generated with http://www.opensky.ca/sudoku
+//! let sudoku: Sudoku = "5...9.38....6....49.38.1.7.28..5.....4.....6.....7..48.5.7.48.97....9....19.8...6".parse().unwrap();
+//!
+//! // The first box is missing a 4, but the second row and the second column
+//! // already contain one, which leaves only (2 / 0)
+//! let step = sudoku.hint().expect("a technique applies");
+//!
+//! assert_eq!(English.format_step(&step),
+//!            "Hidden single: 4 must go in r1c3 because it is the only place in box 1.");
+//! ```
+//!
+//! [LogicalSolver]: crate::logic::LogicalSolver
+
+use crate::House;
+use crate::logic::{SolveStep, Technique};
+
+use std::collections::BTreeMap;
+
+/// Turns a [SolveStep] into a sentence.
+///
+/// Implement this trait to explain steps in another language:
+///
+/// ```
+/// use sudoku::Sudoku;
+/// use sudoku::explain::{cell_name, StepFormatter};
+/// use sudoku::logic::SolveStep;
+///
+/// struct German;
+///
+/// impl StepFormatter for German {
+///     fn format_step(&self, step: &SolveStep) -> String {
+///         let placement = &step.placements[0];
+///         format!("{} kommt in {}.", placement.value, cell_name(placement.x, placement.y))
+///     }
+/// }
+///
+/// // Values generated with http://www.opensky.ca/sudoku
+/// let sudoku: Sudoku = "43...98..19.8....5...724....61.9..2...........2..5.69....637...8....5.31..51...76".parse().unwrap();
+///
+/// assert_eq!(German.format_step(&sudoku.hint().unwrap()), "1 kommt in r1c5.");
+/// ```
+pub trait StepFormatter {
+
+    /// Describe `step` as a sentence.
+    fn format_step(&self, step: &SolveStep) -> String;
+}
+
+/// The [StepFormatter] explaining steps in English.
+///
+/// Steps are expected to look like the ones made by the
+/// [LogicalSolver](crate::logic::LogicalSolver). Steps that do not, e.g.
+/// because their houses are missing, are described by just listing their
+/// placements and eliminations.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct English;
+
+impl StepFormatter for English {
+    fn format_step(&self, step: &SolveStep) -> String {
+        let description = match step.technique {
+            Technique::HiddenSingle => hidden_single(step),
+            Technique::NakedSingle => naked_single(step),
+            Technique::Pointing | Technique::Claiming => locked_candidates(step),
+            Technique::NakedPair | Technique::NakedTriple | Technique::NakedQuad => naked_subset(step),
+            Technique::HiddenPair | Technique::HiddenTriple | Technique::HiddenQuad => hidden_subset(step),
+            Technique::XWing | Technique::Swordfish | Technique::Jellyfish => fish(step),
+            Technique::XYWing => xy_wing(step),
+            Technique::XYZWing => xyz_wing(step),
+        };
+
+        let description = description.unwrap_or_else(|| generic(step));
+
+        format!("{}: {}.", technique_name(step.technique), description)
+    }
+}
+
+/// Get the name of the square at (`x` / `y`) in the `r4c7` notation, giving
+/// the row and then the column, both counted from 1.
+///
+/// ```
+/// use sudoku::explain::cell_name;
+///
+/// assert_eq!(cell_name(6, 3), "r4c7");
+/// ```
+pub fn cell_name(x: usize, y: usize) -> String {
+    format!("r{}c{}", y + 1, x + 1)
+}
+
+/// Get the name of a technique as it is used at the start of a sentence.
+fn technique_name(technique: Technique) -> &'static str {
+    match technique {
+        Technique::HiddenSingle => "Hidden single",
+        Technique::NakedSingle => "Naked single",
+        Technique::Pointing => "Pointing",
+        Technique::Claiming => "Claiming",
+        Technique::NakedPair => "Naked pair",
+        Technique::XWing => "X-Wing",
+        Technique::HiddenPair => "Hidden pair",
+        Technique::NakedTriple => "Naked triple",
+        Technique::Swordfish => "Swordfish",
+        Technique::HiddenTriple => "Hidden triple",
+        Technique::XYWing => "XY-Wing",
+        Technique::XYZWing => "XYZ-Wing",
+        Technique::NakedQuad => "Naked quad",
+        Technique::Jellyfish => "Jellyfish",
+        Technique::HiddenQuad => "Hidden quad",
+    }
+}
+
+fn hidden_single(step: &SolveStep) -> Option<String> {
+    let placement = step.placements.first()?;
+    let house = step.houses.first()?;
+
+    Some(format!("{} must go in {} because it is the only place in {}",
+                 placement.value, cell_name(placement.x, placement.y), house))
+}
+
+fn naked_single(step: &SolveStep) -> Option<String> {
+    let placement = step.placements.first()?;
+
+    Some(format!("{} must go in {} because it is the only candidate left there",
+                 placement.value, cell_name(placement.x, placement.y)))
+}
+
+/// Pointing and claiming: the candidates for a value within the first house
+/// all lie in the second one.
+fn locked_candidates(step: &SolveStep) -> Option<String> {
+    let [house, other] = step.houses[..] else {
+        return None;
+    };
+    let value = step.values.first()?;
+
+    Some(format!("in {}, {} can only go in {}, which are all in {}, so it can be removed from the rest of {}: {}",
+                 house, value, cells(&step.squares), other, other, elimination_cells(step)))
+}
+
+fn naked_subset(step: &SolveStep) -> Option<String> {
+    let house = step.houses.first()?;
+
+    Some(format!("{} can only contain {}, so these values can be removed from the rest of {}: {}",
+                 cells(&step.squares), list(&step.values), house, eliminations(step)))
+}
+
+fn hidden_subset(step: &SolveStep) -> Option<String> {
+    let house = step.houses.first()?;
+
+    Some(format!("in {}, {} can only go in {}, so all other candidates can be removed from these squares: {}",
+                 house, list(&step.values), cells(&step.squares), eliminations(step)))
+}
+
+/// X-Wing, Swordfish and Jellyfish: the first half of the houses are the base
+/// lines, the second half the cover lines.
+fn fish(step: &SolveStep) -> Option<String> {
+    let value = step.values.first()?;
+    if step.houses.is_empty() || step.houses.len() % 2 == 1 {
+        return None;
+    }
+    let (base, cover) = step.houses.split_at(step.houses.len() / 2);
+
+    Some(format!("in {}, {} can only go in {}, so it can be removed from the rest of these lines: {}",
+                 houses(base), value, houses(cover), elimination_cells(step)))
+}
+
+fn xy_wing(step: &SolveStep) -> Option<String> {
+    let [pivot, pincer_0, pincer_1] = step.squares[..] else {
+        return None;
+    };
+    let z = step.eliminations.first()?.value;
+    let pivot_values = step.values.iter().copied().filter(|&value| value != z).collect::<Vec<_>>();
+
+    Some(format!("{} is either {}, so one of {} and {} must be {}, which can be removed from every square seeing both of them: {}",
+                 cell_name(pivot.0, pivot.1), list_with(&pivot_values, "or"), cell_name(pincer_0.0, pincer_0.1),
+                 cell_name(pincer_1.0, pincer_1.1), z, elimination_cells(step)))
+}
+
+fn xyz_wing(step: &SolveStep) -> Option<String> {
+    let z = step.eliminations.first()?.value;
+
+    Some(format!("{} can only contain {}, so one of them must be {}, which can be removed from every square seeing all of them: {}",
+                 cells(&step.squares), list(&step.values), z, elimination_cells(step)))
+}
+
+/// Describe a step by its placements and eliminations only.
+fn generic(step: &SolveStep) -> String {
+    let placements = step.placements
+        .iter()
+        .map(|placement| format!("{} goes in {}", placement.value, cell_name(placement.x, placement.y)))
+        .collect::<Vec<_>>();
+
+    let mut parts = vec![];
+    if !placements.is_empty() {
+        parts.push(list(&placements));
+    }
+    if !step.eliminations.is_empty() {
+        parts.push(format!("remove {}", eliminations(step)));
+    }
+
+    parts.join(", ")
+}
+
+/// List the squares of the eliminations of a step, for steps that eliminate
+/// a single value.
+fn elimination_cells(step: &SolveStep) -> String {
+    let squares = step.eliminations.iter().map(|elimination| (elimination.x, elimination.y)).collect::<Vec<_>>();
+
+    cells(&squares)
+}
+
+/// List the eliminations of a step grouped by value, e.g. `1 from r1c1 and
+/// r1c5; 9 from r1c5`.
+fn eliminations(step: &SolveStep) -> String {
+    let mut by_value = BTreeMap::<u32, Vec<(usize, usize)>>::new();
+    for elimination in &step.eliminations {
+        by_value.entry(elimination.value).or_default().push((elimination.x, elimination.y));
+    }
+
+    by_value
+        .into_iter()
+        .map(|(value, squares)| format!("{} from {}", value, cells(&squares)))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn cells(squares: &[(usize, usize)]) -> String {
+    list(&squares.iter().map(|&(x, y)| cell_name(x, y)).collect::<Vec<_>>())
+}
+
+/// List houses, combining houses of the same kind, e.g. `rows 2 and 7`.
+fn houses(houses: &[House]) -> String {
+    let plural = match houses {
+        [House::Row(_), ..] if houses.iter().all(|house| matches!(house, House::Row(_))) => "rows",
+        [House::Column(_), ..] if houses.iter().all(|house| matches!(house, House::Column(_))) => "columns",
+        [House::Box(_), ..] if houses.iter().all(|house| matches!(house, House::Box(_))) => "boxes",
+        _ => return list(houses),
+    };
+
+    if houses.len() == 1 {
+        return houses[0].to_string();
+    }
+
+    format!("{} {}", plural, list(&houses.iter().map(|house| house.index() + 1).collect::<Vec<_>>()))
+}
+
+/// List items in the form `a, b and c`.
+fn list<T: ToString>(items: &[T]) -> String {
+    list_with(items, "and")
+}
+
+/// List items in the form `a, b <conjunction> c`.
+fn list_with<T: ToString>(items: &[T], conjunction: &str) -> String {
+    let items = items.iter().map(T::to_string).collect::<Vec<_>>();
+
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} {} {}", rest.join(", "), conjunction, last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{House, Sudoku};
+    use crate::logic::{Elimination, LogicalSolver, Placement, SolveStep, Technique};
+
+    use super::{English, StepFormatter};
+
+    fn step(technique: Technique, houses: Vec<House>, squares: Vec<(usize, usize)>, values: Vec<u32>) -> SolveStep {
+        SolveStep {
+            technique,
+            houses,
+            squares,
+            values,
+            placements: vec![],
+            eliminations: vec![],
+        }
+    }
+
+    #[test]
+    fn singles() {
+        let mut hidden_single = step(Technique::HiddenSingle, vec![House::Box(5)], vec![(6, 3)], vec![5]);
+        hidden_single.placements = vec![Placement { x: 6, y: 3, value: 5 }];
+        assert_eq!(English.format_step(&hidden_single),
+                   "Hidden single: 5 must go in r4c7 because it is the only place in box 6.");
+
+        let mut naked_single = step(Technique::NakedSingle, vec![], vec![(0, 8)], vec![2]);
+        naked_single.placements = vec![Placement { x: 0, y: 8, value: 2 }];
+        assert_eq!(English.format_step(&naked_single),
+                   "Naked single: 2 must go in r9c1 because it is the only candidate left there.");
+    }
+
+    #[test]
+    fn locked_candidates() {
+        let mut pointing = step(Technique::Pointing, vec![House::Box(0), House::Row(0)], vec![(1, 0), (2, 0)], vec![3]);
+        pointing.eliminations = vec![Elimination { x: 4, y: 0, value: 3 }, Elimination { x: 6, y: 0, value: 3 }];
+        assert_eq!(English.format_step(&pointing),
+                   "Pointing: in box 1, 3 can only go in r1c2 and r1c3, which are all in row 1, \
+                    so it can be removed from the rest of row 1: r1c5 and r1c7.");
+    }
+
+    #[test]
+    fn subsets() {
+        let mut naked_pair = step(Technique::NakedPair, vec![House::Row(4)], vec![(2, 4), (6, 4)], vec![1, 9]);
+        naked_pair.eliminations = vec![
+            Elimination { x: 0, y: 4, value: 1 },
+            Elimination { x: 1, y: 4, value: 9 },
+            Elimination { x: 3, y: 4, value: 1 },
+        ];
+        assert_eq!(English.format_step(&naked_pair),
+                   "Naked pair: r5c3 and r5c7 can only contain 1 and 9, \
+                    so these values can be removed from the rest of row 5: 1 from r5c1 and r5c4; 9 from r5c2.");
+
+        let mut hidden_triple = step(Technique::HiddenTriple, vec![House::Column(0)], vec![(0, 1), (0, 4), (0, 7)], vec![2, 4, 8]);
+        hidden_triple.eliminations = vec![Elimination { x: 0, y: 4, value: 6 }];
+        assert_eq!(English.format_step(&hidden_triple),
+                   "Hidden triple: in column 1, 2, 4 and 8 can only go in r2c1, r5c1 and r8c1, \
+                    so all other candidates can be removed from these squares: 6 from r5c1.");
+    }
+
+    #[test]
+    fn fish() {
+        let houses = vec![House::Row(1), House::Row(6), House::Column(1), House::Column(7)];
+        let mut x_wing = step(Technique::XWing, houses, vec![(1, 1), (7, 1), (1, 6), (7, 6)], vec![4]);
+        x_wing.eliminations = vec![Elimination { x: 1, y: 0, value: 4 }];
+        assert_eq!(English.format_step(&x_wing),
+                   "X-Wing: in rows 2 and 7, 4 can only go in columns 2 and 8, \
+                    so it can be removed from the rest of these lines: r1c2.");
+    }
+
+    #[test]
+    fn wings() {
+        let mut xy_wing = step(Technique::XYWing, vec![], vec![(0, 0), (4, 0), (1, 2)], vec![1, 2, 7]);
+        xy_wing.eliminations = vec![Elimination { x: 4, y: 2, value: 7 }];
+        assert_eq!(English.format_step(&xy_wing),
+                   "XY-Wing: r1c1 is either 1 or 2, so one of r1c5 and r3c2 must be 7, \
+                    which can be removed from every square seeing both of them: r3c5.");
+
+        let mut xyz_wing = step(Technique::XYZWing, vec![], vec![(0, 0), (1, 0), (0, 1)], vec![1, 2, 7]);
+        xyz_wing.eliminations = vec![Elimination { x: 2, y: 0, value: 7 }];
+        assert_eq!(English.format_step(&xyz_wing),
+                   "XYZ-Wing: r1c1, r1c2 and r2c1 can only contain 1, 2 and 7, so one of them must be 7, \
+                    which can be removed from every square seeing all of them: r1c3.");
+    }
+
+    /// Every step of the solver is explained by its technique, not just by
+    /// listing what it changes.
+    #[test]
+    fn solver_steps() {
+        // Values generated with http://www.opensky.ca/sudoku
+        let sudoku: Sudoku = "7.6.......2...961....65...39..4352..8...9...5..3128..44...82....683...4.......5.1".parse().unwrap();
+
+        for step in LogicalSolver::new(&sudoku) {
+            let sentence = English.format_step(&step);
+            assert!(!sentence.contains("goes in"), "{}", sentence);
+            assert_eq!(step.to_string(), sentence);
+        }
+    }
+
+    #[test]
+    fn malformed_step() {
+        let mut hidden_single = step(Technique::HiddenSingle, vec![], vec![], vec![]);
+        hidden_single.placements = vec![Placement { x: 6, y: 3, value: 5 }];
+        hidden_single.eliminations = vec![Elimination { x: 0, y: 0, value: 5 }];
+
+        assert_eq!(English.format_step(&hidden_single), "Hidden single: 5 goes in r4c7, remove 5 from r1c1.");
+    }
+}
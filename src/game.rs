@@ -0,0 +1,830 @@
+//! Keeping track of a player's progress on a [Sudoku] puzzle.
+//!
+//! A [Sudoku] is just a grid of values, it does not know which of them were
+//! part of the puzzle and which were entered by the player. A [Game] keeps
+//! the original puzzle next to the current grid, so the givens cannot be
+//! changed, and remembers the [PencilMarks] of the player. Every edit is
+//! recorded in a [History], so it can be undone. A [Game] can be saved and
+//! restored with serde, together with its puzzle.
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::game::{Game, GameError};
+//!
+//! // Values generated with http://www.opensky.ca/sudoku
+//! let puzzle: Sudoku = "..1.2.9..9...4..2..2..98.51.17......4..7.6..9......61.13.87..6..7..5...4..5.6.3..".parse().unwrap();
+//!
+//! let mut game = Game::new(puzzle).unwrap();
+//!
+//! // (2 / 0) holds a given
+//! assert_eq!(game.set_value(2, 0, 5), Err(GameError::GivenSquare { x: 2, y: 0 }));
+//!
+//! // 3 is a valid move for now, but not the solution of (0 / 0)
+//! game.set_value(0, 0, 3).unwrap();
+//! assert!(game.grid().is_valid());
+//! assert_eq!(game.mistakes(), vec![(0, 0)]);
+//!
+//! game.undo();
+//! assert_eq!(game.grid(), game.puzzle());
+//! ```
+
+use crate::{SolutionCount, Sudoku, SudokuError};
+use crate::candidates::Candidates;
+use crate::history::{Change, Edit, History};
+use crate::pencil_marks::{PencilMarkMistake, PencilMarks};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt;
+
+/// A puzzle being solved by a player.
+///
+/// The values of the original puzzle are the givens. The player can enter
+/// values into every other square and pencil mark values in empty squares.
+/// Since the puzzle has exactly one solution, every entry can be checked
+/// against it, see [Game::mistakes()].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Game {
+    puzzle: Sudoku,
+    solution: Sudoku,
+    grid: Sudoku,
+    pencil_marks: PencilMarks,
+    history: History,
+    /// The changes made so far within [Game::group_edits()].
+    group: Option<Vec<Change>>,
+}
+
+impl Game {
+
+    /// Start a new [Game] on `puzzle`.
+    ///
+    /// Return [GameError::NoSolution] or [GameError::MultipleSolutions] if
+    /// `puzzle` does not have exactly one solution.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::game::{Game, GameError};
+    ///
+    /// assert_eq!(Game::new(Sudoku::new_empty()), Err(GameError::MultipleSolutions));
+    /// ```
+    pub fn new(puzzle: Sudoku) -> Result<Game, GameError> {
+        let solution = match puzzle.count_solutions(2) {
+            SolutionCount::None => return Err(GameError::NoSolution),
+            SolutionCount::Unique => puzzle.find_solution().expect("the puzzle has a solution"),
+            SolutionCount::Multiple(_) => return Err(GameError::MultipleSolutions),
+        };
+
+        Ok(Game {
+            puzzle,
+            solution,
+            grid: puzzle,
+            pencil_marks: PencilMarks::new(),
+            history: History::new(),
+            group: None,
+        })
+    }
+
+    /// Get the original puzzle, which contains only the givens.
+    pub fn puzzle(&self) -> &Sudoku {
+        &self.puzzle
+    }
+
+    /// Get the solution of the puzzle.
+    pub fn solution(&self) -> &Sudoku {
+        &self.solution
+    }
+
+    /// Get the current grid, with the givens and all values entered by the
+    /// player.
+    pub fn grid(&self) -> &Sudoku {
+        &self.grid
+    }
+
+    /// Check if the square at (`x` / `y`) holds a given.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn is_given(&self, x: usize, y: usize) -> bool {
+        self.puzzle.get_value(x, y) != 0
+    }
+
+    /// Enter `value` into the square at (`x` / `y`), or clear the square if
+    /// `value` is 0.
+    ///
+    /// Return [GameError::GivenSquare] if the square holds a given and
+    /// [GameError::Sudoku] if the coordinates or the value are out of bounds.
+    /// Like [Sudoku::try_set_value()], this does not check whether the value
+    /// conflicts with other values.
+    ///
+    /// Pencil marks are kept when a value is entered, so they show up again
+    /// when the square is cleared.
+    pub fn set_value(&mut self, x: usize, y: usize, value: u32) -> Result<(), GameError> {
+        if self.puzzle.try_get_value(x, y)? != 0 {
+            return Err(GameError::GivenSquare { x, y });
+        }
+        if value > 9 {
+            return Err(SudokuError::InvalidValue(value).into());
+        }
+
+        let before = self.grid.get_value(x, y);
+        if before != value {
+            self.commit(vec![Change::Value { x, y, before, after: value }]);
+        }
+
+        Ok(())
+    }
+
+    /// Clear the value the player entered into the square at (`x` / `y`).
+    ///
+    /// Return the same errors as [Game::set_value()].
+    pub fn clear_value(&mut self, x: usize, y: usize) -> Result<(), GameError> {
+        self.set_value(x, y, 0)
+    }
+
+    /// Check if `value` is pencil marked in the square at (`x` / `y`).
+    ///
+    /// Panics if the coordinates or the value are out of bounds.
+    pub fn has_pencil_mark(&self, x: usize, y: usize, value: u32) -> bool {
+        self.pencil_marks.contains(x, y, value)
+    }
+
+    /// Get the pencil marks of all squares.
+    pub fn pencil_marks(&self) -> &PencilMarks {
+        &self.pencil_marks
+    }
+
+    /// Add or remove the pencil mark for `value` in the square at
+    /// (`x` / `y`).
+    ///
+    /// Pencil marks can be made in every square that does not hold a given,
+    /// but they are only meaningful while the square is empty.
+    ///
+    /// Return [GameError::GivenSquare] if the square holds a given and
+    /// [GameError::Sudoku] if the coordinates or the value are out of bounds.
+    pub fn set_pencil_mark(&mut self, x: usize, y: usize, value: u32, marked: bool) -> Result<(), GameError> {
+        self.check_pencil_mark(x, y, value)?;
+
+        let mut marks = self.pencil_marks.get(x, y);
+        if marked {
+            marks.insert(value);
+        } else {
+            marks.remove(value);
+        }
+
+        let changes = self.pencil_mark_changes(x, y, marks);
+        self.commit(changes);
+
+        Ok(())
+    }
+
+    /// Toggle the pencil mark for `value` in the square at (`x` / `y`).
+    ///
+    /// Return the same errors as [Game::set_pencil_mark()].
+    pub fn toggle_pencil_mark(&mut self, x: usize, y: usize, value: u32) -> Result<(), GameError> {
+        self.check_pencil_mark(x, y, value)?;
+
+        let mut marks = self.pencil_marks.get(x, y);
+        marks.toggle(value);
+
+        let changes = self.pencil_mark_changes(x, y, marks);
+        self.commit(changes);
+
+        Ok(())
+    }
+
+    /// Return an error if `value` cannot be pencil marked in the square at
+    /// (`x` / `y`), see [Game::set_pencil_mark()].
+    fn check_pencil_mark(&self, x: usize, y: usize, value: u32) -> Result<(), GameError> {
+        if self.puzzle.try_get_value(x, y)? != 0 {
+            return Err(GameError::GivenSquare { x, y });
+        }
+        if !(1..=9).contains(&value) {
+            return Err(SudokuError::InvalidValue(value).into());
+        }
+
+        Ok(())
+    }
+
+    /// Remove all pencil marks from the square at (`x` / `y`).
+    ///
+    /// A square that was pencil marked before still counts as pencil marked
+    /// afterwards, so its solution is reported as wrongly removed by
+    /// [Game::pencil_mark_mistakes()].
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn clear_pencil_marks(&mut self, x: usize, y: usize) {
+        let changes = self.pencil_mark_changes(x, y, Candidates::new());
+        self.commit(changes);
+    }
+
+    /// Pencil mark every value in every empty square that does not appear in
+    /// the same row, column or box yet, replacing all previous pencil marks.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::game::Game;
+    ///
+    /// // Values generated with http://www.opensky.ca/sudoku
+    /// let puzzle: Sudoku = "43...98..19.8....5...724....61.9..2...........2..5.69....637...8....5.31..51...76".parse().unwrap();
+    ///
+    /// let mut game = Game::new(puzzle).unwrap();
+    /// game.fill_pencil_marks();
+    ///
+    /// assert!(game.has_pencil_mark(8, 0, 2));
+    /// assert!(game.has_pencil_mark(8, 0, 7));
+    /// // The first row already contains a 9
+    /// assert!(!game.has_pencil_mark(8, 0, 9));
+    /// ```
+    pub fn fill_pencil_marks(&mut self) {
+        let candidates = self.grid.candidates();
+        let mut changes = Vec::new();
+
+        for y in 0..9 {
+            for x in 0..9 {
+                changes.extend(self.pencil_mark_changes(x, y, candidates[x + y * 9]));
+            }
+        }
+
+        self.commit(changes);
+    }
+
+    /// Get all pencil marks that contradict the solution, see
+    /// [PencilMarks::check()].
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::game::Game;
+    /// use sudoku::pencil_marks::PencilMarkMistake;
+    ///
+    /// // Values generated with http://www.opensky.ca/sudoku
+    /// let puzzle: Sudoku = ".3.....7...79...424..7..85....875.6....1.2....8.463....12..7..496...17...4.....3.".parse().unwrap();
+    /// let mut game = Game::new(puzzle).unwrap();
+    ///
+    /// game.fill_pencil_marks();
+    /// // 2 is the solution of (3 / 0)
+    /// game.set_pencil_mark(3, 0, 2, false).unwrap();
+    ///
+    /// assert_eq!(game.pencil_mark_mistakes(), vec![PencilMarkMistake::WronglyRemoved { x: 3, y: 0, value: 2 }]);
+    /// ```
+    pub fn pencil_mark_mistakes(&self) -> Vec<PencilMarkMistake> {
+        self.pencil_marks.check(&self.grid, &self.solution)
+    }
+
+    /// Get the coordinates (x, y) of all squares where the player entered a
+    /// value that differs from the solution, going row by row.
+    ///
+    /// Since the solution is unique, every such value is a mistake, even if
+    /// it does not conflict with any other value yet.
+    pub fn mistakes(&self) -> Vec<(usize, usize)> {
+        (0..9)
+            .flat_map(|y| (0..9).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_mistake(x, y))
+            .collect()
+    }
+
+    /// Check if the player entered a value into the square at (`x` / `y`)
+    /// that differs from the solution.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn is_mistake(&self, x: usize, y: usize) -> bool {
+        let value = self.grid.get_value(x, y);
+
+        value != 0 && value != self.solution.get_value(x, y)
+    }
+
+    /// Check if the grid is completely and correctly filled in.
+    pub fn is_solved(&self) -> bool {
+        self.grid == self.solution
+    }
+
+    /// Remove all values entered by the player and all pencil marks.
+    ///
+    /// This is a single edit, which can be undone like any other.
+    pub fn reset(&mut self) {
+        let mut changes = Vec::new();
+
+        for y in 0..9 {
+            for x in 0..9 {
+                let before = self.grid.get_value(x, y);
+                if !self.is_given(x, y) && before != 0 {
+                    changes.push(Change::Value { x, y, before, after: 0 });
+                }
+                changes.extend(self.pencil_mark_changes(x, y, Candidates::new()));
+            }
+        }
+
+        self.commit(changes);
+    }
+
+    /// Get the [History] of all edits made so far.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Make all edits within `edit` a single edit, so they are undone and
+    /// redone together.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::game::Game;
+    ///
+    /// // Values generated with http://www.opensky.ca/sudoku
+    /// let puzzle: Sudoku = "7.6.......2...961....65...39..4352..8...9...5..3128..44...82....683...4.......5.1".parse().unwrap();
+    /// let mut game = Game::new(puzzle).unwrap();
+    ///
+    /// game.group_edits(|game| {
+    ///     game.set_value(1, 0, 8)?;
+    ///     game.set_value(3, 0, 2)
+    /// }).unwrap();
+    ///
+    /// game.undo();
+    /// assert_eq!(game.grid(), game.puzzle());
+    /// ```
+    ///
+    /// The edits made before an error are kept, together they are still a
+    /// single edit.
+    pub fn group_edits<T>(&mut self, edit: impl FnOnce(&mut Game) -> T) -> T {
+        // Nested groups just become part of the outer group.
+        if self.group.is_some() {
+            return edit(self);
+        }
+
+        self.group = Some(Vec::new());
+        let result = edit(self);
+        let changes = self.group.take().unwrap_or_default();
+        self.history.record(Edit::new(changes));
+
+        result
+    }
+
+    /// Undo the last edit.
+    ///
+    /// Return `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Game { history, grid, pencil_marks, .. } = self;
+
+        match history.undo() {
+            Some(edit) => {
+                for change in edit.changes().iter().rev() {
+                    apply(grid, pencil_marks, change.inverse());
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Redo the edit that was undone last.
+    ///
+    /// Return `false` if there was nothing to redo. If edits were made after
+    /// undoing, the undone edits are kept in another branch of the [History],
+    /// see [Game::redo_branch()].
+    pub fn redo(&mut self) -> bool {
+        let Game { history, grid, pencil_marks, .. } = self;
+
+        match history.redo() {
+            Some(edit) => {
+                edit.changes().iter().for_each(|&change| apply(grid, pencil_marks, change));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Redo the edit of the given branch, see [History::redo_branches()].
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::game::Game;
+    ///
+    /// // Values generated with http://www.opensky.ca/sudoku
+    /// let puzzle: Sudoku = "5...9.38....6....49.38.1.7.28..5.....4.....6.....7..48.5.7.48.97....9....19.8...6".parse().unwrap();
+    /// let mut game = Game::new(puzzle).unwrap();
+    ///
+    /// // Both 2 and 4 are candidates of (3 / 0)
+    /// game.set_value(3, 0, 2).unwrap();
+    /// game.undo();
+    /// game.set_value(3, 0, 4).unwrap();
+    /// game.undo();
+    ///
+    /// assert_eq!(game.history().redo_branches().len(), 2);
+    ///
+    /// game.redo_branch(0);
+    /// assert_eq!(game.grid().get_value(3, 0), 2);
+    /// ```
+    ///
+    /// Return `false` if there is no such branch.
+    pub fn redo_branch(&mut self, branch: usize) -> bool {
+        let Game { history, grid, pencil_marks, .. } = self;
+
+        match history.redo_branch(branch) {
+            Some(edit) => {
+                edit.changes().iter().for_each(|&change| apply(grid, pencil_marks, change));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Undo edits until no value entered by the player differs from the
+    /// solution (see [Game::mistakes()]).
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::game::Game;
+    ///
+    /// // Values generated with http://www.opensky.ca/sudoku
+    /// let puzzle: Sudoku = "....7581...24..7......3..4.2....7.53.1.2.9.8.59.3....4.2..6......9..43...4871....".parse().unwrap();
+    /// let mut game = Game::new(puzzle).unwrap();
+    ///
+    /// game.set_value(0, 0, 9).unwrap(); // correct
+    /// game.set_value(1, 0, 6).unwrap(); // wrong
+    /// game.set_value(2, 0, 3).unwrap(); // wrong
+    ///
+    /// assert_eq!(game.undo_to_last_consistent(), 2);
+    /// assert_eq!(game.grid().get_value(0, 0), 9);
+    /// assert!(game.mistakes().is_empty());
+    /// ```
+    ///
+    /// Return the number of edits that were undone. They can be redone like
+    /// any other undone edits.
+    pub fn undo_to_last_consistent(&mut self) -> usize {
+        let mut undone = 0;
+
+        while !self.mistakes().is_empty() && self.undo() {
+            undone += 1;
+        }
+
+        undone
+    }
+
+    /// Get the changes setting the pencil marks of the square at (`x` / `y`)
+    /// to `marks`.
+    ///
+    /// Adding a mark to a square that was never pencil marked makes it count
+    /// as pencil marked, see [PencilMarks::is_marked()].
+    fn pencil_mark_changes(&self, x: usize, y: usize, marks: Candidates) -> Vec<Change> {
+        let before = self.pencil_marks.get(x, y);
+
+        let mut changes = Vec::new();
+        if !self.pencil_marks.is_marked(x, y) && !marks.is_empty() {
+            changes.push(Change::PencilMarked { x, y, marked: true });
+        }
+
+        changes.extend(
+            (before ^ marks)
+                .iter()
+                .map(|value| Change::PencilMark { x, y, value, marked: marks.contains(value) })
+        );
+
+        changes
+    }
+
+    /// Apply `changes` and record them as a single edit, or add them to the
+    /// current group (see [Game::group_edits()]).
+    fn commit(&mut self, changes: Vec<Change>) {
+        for &change in &changes {
+            apply(&mut self.grid, &mut self.pencil_marks, change);
+        }
+
+        match &mut self.group {
+            Some(group) => group.extend(changes),
+            None => self.history.record(Edit::new(changes)),
+        }
+    }
+}
+
+/// The error type for the operations on a [Game].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameError {
+    /// The puzzle has no solution.
+    NoSolution,
+    /// The puzzle has more than one solution, so entries cannot be checked.
+    MultipleSolutions,
+    /// The square at (`x` / `y`) holds a given, which cannot be changed.
+    GivenSquare {
+        /// The x coordinate of the square.
+        x: usize,
+        /// The y coordinate of the square.
+        y: usize,
+    },
+    /// The coordinates or the value are out of bounds.
+    Sudoku(SudokuError),
+}
+
+impl From<SudokuError> for GameError {
+    fn from(error: SudokuError) -> GameError {
+        GameError::Sudoku(error)
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NoSolution => write!(f, "the puzzle has no solution"),
+            GameError::MultipleSolutions => write!(f, "the puzzle has more than one solution"),
+            GameError::GivenSquare { x, y } => write!(f, "the square at (x = {}, y = {}) holds a given", x, y),
+            GameError::Sudoku(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameError::Sudoku(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Apply a single [Change] to the grid and the pencil marks of a [Game].
+fn apply(grid: &mut Sudoku, pencil_marks: &mut PencilMarks, change: Change) {
+    match change {
+        Change::Value { x, y, after, .. } => grid.set_value(x, y, after),
+        Change::PencilMark { x, y, value, marked: true } => { pencil_marks.add(x, y, value); },
+        Change::PencilMark { x, y, value, marked: false } => { pencil_marks.remove(x, y, value); },
+        Change::PencilMarked { x, y, marked: true } => pencil_marks.set(x, y, pencil_marks.get(x, y)),
+        Change::PencilMarked { x, y, marked: false } => pencil_marks.unmark(x, y),
+    }
+}
+
+/// The state of a [Game] that is serialized.
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    puzzle: Sudoku,
+    grid: Sudoku,
+    pencil_marks: PencilMarks,
+}
+
+/// A [Game] is serialized as its puzzle, the current grid and the pencil
+/// marks. The [History] is not serialized.
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedGame {
+            puzzle: self.puzzle,
+            grid: self.grid,
+            pencil_marks: self.pencil_marks.clone(),
+        }.serialize(serializer)
+    }
+}
+
+/// A deserialized [Game] starts with an empty [History]. Deserializing fails
+/// if the puzzle does not have exactly one solution or if the grid does not
+/// contain all givens of the puzzle.
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Game, D::Error> {
+        let saved = SavedGame::deserialize(deserializer)?;
+        let mut game = Game::new(saved.puzzle).map_err(serde::de::Error::custom)?;
+
+        for y in 0..9 {
+            for x in 0..9 {
+                if game.is_given(x, y) && saved.grid.get_value(x, y) != saved.puzzle.get_value(x, y) {
+                    return Err(serde::de::Error::custom(GameError::GivenSquare { x, y }));
+                }
+            }
+        }
+
+        game.grid = saved.grid;
+        game.pencil_marks = saved.pencil_marks;
+
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{Sudoku, SudokuError};
+    use crate::pencil_marks::PencilMarkMistake;
+    use crate::tests::PUZZLE;
+
+    use super::{Game, GameError};
+
+    fn game() -> Game {
+        Game::new(PUZZLE.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn requires_unique_solution() {
+        assert_eq!(Game::new(Sudoku::new_empty()), Err(GameError::MultipleSolutions));
+
+        let invalid: Sudoku = ("11".to_owned() + &".".repeat(79)).parse().unwrap();
+        assert_eq!(Game::new(invalid), Err(GameError::NoSolution));
+    }
+
+    #[test]
+    fn givens_are_immutable() {
+        let mut game = game();
+
+        assert!(game.is_given(2, 0));
+        assert_eq!(game.set_value(2, 0, 1), Err(GameError::GivenSquare { x: 2, y: 0 }));
+        assert_eq!(game.clear_value(2, 0), Err(GameError::GivenSquare { x: 2, y: 0 }));
+        assert_eq!(game.toggle_pencil_mark(2, 0, 3), Err(GameError::GivenSquare { x: 2, y: 0 }));
+        assert_eq!(game.grid(), game.puzzle());
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut game = game();
+
+        assert_eq!(game.set_value(9, 0, 1), Err(GameError::Sudoku(SudokuError::InvalidCoordinates { x: 9, y: 0 })));
+        assert_eq!(game.set_value(0, 0, 10), Err(GameError::Sudoku(SudokuError::InvalidValue(10))));
+        assert_eq!(game.toggle_pencil_mark(0, 0, 0), Err(GameError::Sudoku(SudokuError::InvalidValue(0))));
+        assert_eq!(game.toggle_pencil_mark(0, 9, 1), Err(GameError::Sudoku(SudokuError::InvalidCoordinates { x: 0, y: 9 })));
+    }
+
+    #[test]
+    fn entries_and_mistakes() {
+        let mut game = game();
+        let solution = *game.solution();
+
+        game.set_value(0, 0, solution.get_value(0, 0)).unwrap();
+        game.set_value(1, 0, solution.get_value(1, 0) % 9 + 1).unwrap();
+        assert_eq!(game.mistakes(), vec![(1, 0)]);
+        assert!(!game.is_mistake(0, 0));
+        assert!(!game.is_mistake(3, 0));
+
+        game.clear_value(1, 0).unwrap();
+        assert!(game.mistakes().is_empty());
+        assert!(!game.is_solved());
+
+        for (x, y) in (0..9).flat_map(|y| (0..9).map(move |x| (x, y))) {
+            if !game.is_given(x, y) {
+                game.set_value(x, y, solution.get_value(x, y)).unwrap();
+            }
+        }
+        assert!(game.is_solved());
+
+        game.reset();
+        assert_eq!(game.grid(), game.puzzle());
+    }
+
+    #[test]
+    fn pencil_marks() {
+        let mut game = game();
+
+        game.toggle_pencil_mark(0, 0, 3).unwrap();
+        game.set_pencil_mark(0, 0, 8, true).unwrap();
+        assert!(game.has_pencil_mark(0, 0, 3));
+        assert!(game.has_pencil_mark(0, 0, 8));
+
+        game.toggle_pencil_mark(0, 0, 3).unwrap();
+        assert!(!game.has_pencil_mark(0, 0, 3));
+
+        // pencil marks stay hidden beneath an entered value
+        game.set_value(0, 0, 7).unwrap();
+        game.clear_value(0, 0).unwrap();
+        assert!(game.has_pencil_mark(0, 0, 8));
+
+        game.clear_pencil_marks(0, 0);
+        assert!(!game.has_pencil_mark(0, 0, 8));
+
+        game.fill_pencil_marks();
+        let puzzle = *game.puzzle();
+        for value in 1..=9 {
+            let possible = (0..9).all(|i| puzzle.get_value(i, 0) != value && puzzle.get_value(0, i) != value) &&
+                (0..3).all(|x| (0..3).all(|y| puzzle.get_value(x, y) != value));
+            assert_eq!(game.has_pencil_mark(0, 0, value), possible, "{}", value);
+        }
+    }
+
+    #[test]
+    fn pencil_mark_mistakes() {
+        let mut game = game();
+        game.fill_pencil_marks();
+        assert!(game.pencil_mark_mistakes().is_empty());
+
+        game.set_pencil_mark(3, 0, 3, false).unwrap();
+        assert_eq!(game.pencil_mark_mistakes(), vec![PencilMarkMistake::WronglyRemoved { x: 3, y: 0, value: 3 }]);
+
+        // 5 is still marked in the squares seeing (0 / 0)
+        game.set_value(0, 0, 5).unwrap();
+        assert!(game.pencil_mark_mistakes().contains(&PencilMarkMistake::Impossible { x: 1, y: 0, value: 5 }));
+        assert!(game.pencil_mark_mistakes().contains(&PencilMarkMistake::Impossible { x: 0, y: 3, value: 5 }));
+    }
+
+    #[test]
+    fn removing_all_pencil_marks() {
+        let mut game = game();
+
+        // 7 is the solution of (5 / 0)
+        let value = game.grid().candidates_at(5, 0).iter().find(|&value| value != 7).unwrap();
+        game.toggle_pencil_mark(5, 0, value).unwrap();
+        assert_eq!(game.pencil_mark_mistakes(), vec![PencilMarkMistake::WronglyRemoved { x: 5, y: 0, value: 7 }]);
+
+        game.clear_pencil_marks(5, 0);
+        assert!(game.pencil_marks().is_marked(5, 0));
+        assert_eq!(game.pencil_mark_mistakes(), vec![PencilMarkMistake::WronglyRemoved { x: 5, y: 0, value: 7 }]);
+
+        // undoing the first mark forgets that the square was pencil marked
+        game.undo();
+        game.undo();
+        assert!(!game.pencil_marks().is_marked(5, 0));
+        assert!(game.pencil_mark_mistakes().is_empty());
+
+        // clearing a square that was never pencil marked does nothing
+        game.clear_pencil_marks(5, 0);
+        assert!(!game.pencil_marks().is_marked(5, 0));
+    }
+
+    #[test]
+    fn serde() {
+        let mut game = game();
+        game.set_value(0, 0, 5).unwrap();
+        game.toggle_pencil_mark(1, 0, 4).unwrap();
+
+        let json = serde_json::to_string(&game).unwrap();
+        let restored: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.grid(), game.grid());
+        assert_eq!(restored.pencil_marks(), game.pencil_marks());
+        assert!(!restored.history().can_undo());
+
+        // the grid must keep the givens
+        let json = json.replacen(r#""grid":"501"#, r#""grid":"503"#, 1);
+        assert!(json.contains("503"));
+        assert!(serde_json::from_str::<Game>(&json).is_err());
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut game = game();
+        assert!(!game.undo());
+
+        game.set_value(0, 0, 5).unwrap();
+        game.toggle_pencil_mark(1, 0, 4).unwrap();
+        game.fill_pencil_marks();
+        let filled = game.clone();
+
+        // setting a value to what it already is does not count as an edit
+        game.set_value(0, 0, 5).unwrap();
+        assert_eq!(game.history().edits().len(), 3);
+
+        assert!(game.undo());
+        assert!(game.has_pencil_mark(1, 0, 4));
+        assert!(!game.has_pencil_mark(3, 0, 3));
+
+        assert!(game.undo());
+        assert!(!game.has_pencil_mark(1, 0, 4));
+
+        assert!(game.undo());
+        assert_eq!(game.grid(), game.puzzle());
+
+        while game.redo() {}
+        assert_eq!(game.grid(), filled.grid());
+        assert!((1..=9).all(|value| game.has_pencil_mark(3, 0, value) == filled.has_pencil_mark(3, 0, value)));
+
+        game.reset();
+        assert_eq!(game.grid(), game.puzzle());
+        assert!(!game.has_pencil_mark(1, 0, 4));
+
+        game.undo();
+        assert_eq!(game.grid().get_value(0, 0), 5);
+        assert!(game.has_pencil_mark(1, 0, 4));
+    }
+
+    #[test]
+    fn grouped_edits() {
+        let mut game = game();
+
+        let result = game.group_edits(|game| {
+            game.set_value(0, 0, 5)?;
+            game.group_edits(|game| game.toggle_pencil_mark(1, 0, 4))?;
+            game.set_value(2, 0, 7)
+        });
+        assert_eq!(result, Err(GameError::GivenSquare { x: 2, y: 0 }));
+        assert_eq!(game.history().edits().len(), 1);
+
+        game.undo();
+        assert_eq!(game.grid(), game.puzzle());
+        assert!(!game.has_pencil_mark(1, 0, 4));
+    }
+
+    #[test]
+    fn branches_and_consistency() {
+        let mut game = game();
+        let solution = *game.solution();
+
+        game.set_value(0, 0, solution.get_value(0, 0)).unwrap();
+        game.set_value(1, 0, solution.get_value(1, 0) % 9 + 1).unwrap();
+        game.toggle_pencil_mark(3, 0, 1).unwrap();
+        assert_eq!(game.undo_to_last_consistent(), 2);
+        assert_eq!(game.undo_to_last_consistent(), 0);
+        assert!(game.mistakes().is_empty());
+        assert!(!game.has_pencil_mark(3, 0, 1));
+
+        game.set_value(1, 0, solution.get_value(1, 0)).unwrap();
+        game.undo();
+        assert_eq!(game.history().redo_branches().len(), 2);
+
+        assert!(game.redo_branch(0));
+        assert_eq!(game.mistakes(), vec![(1, 0)]);
+        assert!(game.redo());
+        assert!(game.has_pencil_mark(3, 0, 1));
+
+        game.undo();
+        game.undo();
+        assert!(!game.redo_branch(2));
+        assert!(game.redo_branch(1));
+        assert!(game.mistakes().is_empty());
+    }
+}
@@ -0,0 +1,567 @@
+//! Generating new [Sudoku] puzzles.
+//!
+//! ```
+//! use sudoku::generator::Generator;
+//!
+//! let mut generator = Generator::with_seed(42);
+//!
+//! let puzzle = generator.generate();
+//!
+//! assert!(puzzle.has_unique_solution());
+//! ```
+
+use crate::{Sudoku, NUM_SQUARES};
+use crate::rating::{Difficulty, Rating};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use std::ops::{Bound, RangeBounds};
+
+/// The smallest number of clues a [Sudoku] with a unique solution can have.
+pub const MIN_CLUES: usize = 17;
+
+/// The default for [Generator::max_attempts()].
+pub const DEFAULT_MAX_ATTEMPTS: usize = 100;
+
+/// Generates [Sudoku] puzzles with a unique solution.
+///
+/// A puzzle is generated by first building a random solved grid and then
+/// removing clues in random order as long as the puzzle keeps having exactly
+/// one solution. If a [Symmetry] is set, clues are removed in groups so that
+/// the layout of the clues is symmetric.
+///
+/// All randomness comes from a seedable random number generator, so two
+/// [Generator]s created with [Generator::with_seed()] using the same seed
+/// generate the same puzzles (as long as the version of this crate and its
+/// dependencies stay the same).
+#[derive(Clone, Debug)]
+pub struct Generator {
+    rng: StdRng,
+    target_clues: usize,
+    symmetry: Symmetry,
+    max_attempts: usize,
+}
+
+impl Generator {
+
+    /// Initialize a new [Generator] seeded from the operating system's source
+    /// of randomness.
+    pub fn new() -> Generator {
+        Generator::from_rng(StdRng::from_entropy())
+    }
+
+    /// Initialize a new [Generator] with a fixed seed.
+    ///
+    /// ```
+    /// use sudoku::generator::Generator;
+    ///
+    /// let puzzle_0 = Generator::with_seed(7).target_clues(30).generate();
+    /// let puzzle_1 = Generator::with_seed(7).target_clues(30).generate();
+    ///
+    /// assert_eq!(puzzle_0, puzzle_1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Generator {
+        Generator::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Generator {
+        Generator {
+            rng,
+            target_clues: MIN_CLUES,
+            symmetry: Symmetry::None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Set the number of clues the generated puzzles should have.
+    ///
+    /// The [Generator] stops removing clues as soon as this number is reached.
+    /// It is a lower bound only: if no more clues can be removed without
+    /// losing the uniqueness of the solution, the generated puzzle has more
+    /// clues than requested.
+    ///
+    /// ```
+    /// use sudoku::generator::Generator;
+    ///
+    /// let puzzle = Generator::with_seed(1).target_clues(40).generate();
+    ///
+    /// assert_eq!(puzzle.num_empty_squares(), sudoku::NUM_SQUARES - 40);
+    /// ```
+    ///
+    /// The default is [MIN_CLUES], which means that clues are removed until
+    /// none can be removed anymore. Values smaller than [MIN_CLUES] are
+    /// treated like [MIN_CLUES].
+    pub fn target_clues(mut self, target_clues: usize) -> Generator {
+        self.target_clues = target_clues.clamp(MIN_CLUES, NUM_SQUARES);
+        self
+    }
+
+    /// Set the [Symmetry] of the clue layout of the generated puzzles.
+    ///
+    /// ```
+    /// use sudoku::generator::{Generator, Symmetry};
+    ///
+    /// let puzzle = Generator::with_seed(3).symmetry(Symmetry::Rotational180).generate();
+    ///
+    /// assert!(Symmetry::Rotational180.is_symmetric(&puzzle));
+    /// ```
+    ///
+    /// The default is [Symmetry::None]. Symmetric puzzles usually need a few
+    /// more clues than asymmetric ones, because clues can only be removed in
+    /// groups.
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Generator {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Set how many puzzles [Generator::generate_with_difficulty()] may
+    /// generate before giving up.
+    ///
+    /// The default is [DEFAULT_MAX_ATTEMPTS].
+    pub fn max_attempts(mut self, max_attempts: usize) -> Generator {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Generate a random solved [Sudoku].
+    pub fn generate_solved(&mut self) -> Sudoku {
+        let mut grid = [0; NUM_SQUARES];
+
+        let filled = fill_randomly(&mut grid, 0, &mut self.rng);
+        debug_assert!(filled, "an empty grid can always be filled");
+
+        Sudoku::new_from_array(grid)
+    }
+
+    /// Generate a random puzzle with a unique solution.
+    pub fn generate(&mut self) -> Sudoku {
+        let solution = self.generate_solved();
+
+        self.remove_clues(solution)
+    }
+
+    /// Generate a random puzzle with a unique solution whose difficulty is
+    /// within the given band.
+    ///
+    /// ```
+    /// use sudoku::generator::Generator;
+    /// use sudoku::rating::Difficulty;
+    ///
+    /// let mut generator = Generator::with_seed(4);
+    ///
+    /// let generated = generator
+    ///     .generate_with_difficulty(Difficulty::Medium..=Difficulty::Hard)
+    ///     .expect("100 attempts are plenty for this");
+    ///
+    /// assert!((Difficulty::Medium..=Difficulty::Hard).contains(&generated.rating.difficulty));
+    /// assert_eq!(generated.puzzle.rate(), Some(generated.rating));
+    /// ```
+    ///
+    /// Puzzles are generated like with [Generator::generate()] and rated
+    /// with [Sudoku::rate()]. A puzzle that is too hard is refined by adding
+    /// back clues from its solution (respecting the [Symmetry]) until it is
+    /// rated within the band or becomes too easy. A puzzle that is too easy is
+    /// thrown away.
+    ///
+    /// Return `None` if no puzzle within the band was found after generating
+    /// [Generator::max_attempts()] puzzles.
+    pub fn generate_with_difficulty(&mut self, band: impl RangeBounds<Difficulty>) -> Option<GeneratedPuzzle> {
+        for attempt in 1..=self.max_attempts {
+            let solution = self.generate_solved();
+            let mut puzzle = self.remove_clues(solution);
+            let mut rating = puzzle.rate().expect("generated puzzles are solvable");
+
+            // Empty orbits, in the order in which they are filled back in
+            let mut empty_orbits = self
+                .symmetry
+                .orbits()
+                .into_iter()
+                .filter(|orbit| orbit.iter().all(|&(x, y)| puzzle.get_value(x, y) == 0))
+                .collect::<Vec<_>>();
+            empty_orbits.shuffle(&mut self.rng);
+
+            while is_above(&band, rating.difficulty) {
+                let orbit = match empty_orbits.pop() {
+                    Some(orbit) => orbit,
+                    None => break,
+                };
+
+                for (x, y) in orbit {
+                    puzzle.set_value(x, y, solution.get_value(x, y));
+                }
+                rating = puzzle.rate().expect("generated puzzles are solvable");
+            }
+
+            if band.contains(&rating.difficulty) {
+                return Some(GeneratedPuzzle {
+                    puzzle,
+                    rating,
+                    attempts: attempt,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Remove clues from `puzzle` in random order as long as its solution
+    /// stays unique, until the target number of clues is reached.
+    ///
+    /// Clues are removed one orbit of the symmetry at a time. An orbit is only
+    /// removed if that does not take the number of clues below the target.
+    fn remove_clues(&mut self, mut puzzle: Sudoku) -> Sudoku {
+        let mut orbits = self.symmetry.orbits();
+        orbits.shuffle(&mut self.rng);
+
+        let mut num_clues = NUM_SQUARES - puzzle.num_empty_squares();
+
+        for orbit in orbits {
+            if num_clues <= self.target_clues {
+                break;
+            }
+
+            let values = orbit.iter().map(|&(x, y)| puzzle.get_value(x, y)).collect::<Vec<_>>();
+            let num_removed = values.iter().filter(|&&value| value != 0).count();
+            if num_removed == 0 || num_clues - num_removed < self.target_clues {
+                continue;
+            }
+
+            for &(x, y) in &orbit {
+                puzzle.set_value(x, y, 0);
+            }
+
+            if puzzle.has_unique_solution() {
+                num_clues -= num_removed;
+            } else {
+                for (&(x, y), &value) in orbit.iter().zip(&values) {
+                    puzzle.set_value(x, y, value);
+                }
+            }
+        }
+
+        puzzle
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Generator {
+        Generator::new()
+    }
+}
+
+/// A puzzle generated by [Generator::generate_with_difficulty()].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneratedPuzzle {
+    /// The generated puzzle.
+    pub puzzle: Sudoku,
+    /// The rating of `puzzle`.
+    pub rating: Rating,
+    /// How many puzzles had to be generated to find this one.
+    pub attempts: usize,
+}
+
+/// Check if `difficulty` is harder than every difficulty within `band`.
+fn is_above(band: &impl RangeBounds<Difficulty>, difficulty: Difficulty) -> bool {
+    match band.end_bound() {
+        Bound::Included(&end) => difficulty > end,
+        Bound::Excluded(&end) => difficulty >= end,
+        Bound::Unbounded => false,
+    }
+}
+
+/// The symmetry of the clue layout of a puzzle.
+///
+/// A puzzle has a symmetry if mapping every square to its counterpart (e.g.
+/// the square on the opposite side of the center for [Symmetry::Rotational180])
+/// maps clues to clues and empty squares to empty squares. Only the layout is
+/// symmetric, not the values.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Symmetry {
+    /// No symmetry.
+    #[default]
+    None,
+    /// The layout looks the same after turning the grid by 180 degrees.
+    Rotational180,
+    /// The layout looks the same after turning the grid by 90 degrees.
+    Rotational90,
+    /// The top half of the layout mirrors the bottom half.
+    HorizontalMirror,
+    /// The left half of the layout mirrors the right half.
+    VerticalMirror,
+    /// The layout is mirrored along the diagonal from the top left to the
+    /// bottom right corner.
+    Diagonal,
+    /// The layout is mirrored along the diagonal from the top right to the
+    /// bottom left corner.
+    AntiDiagonal,
+}
+
+impl Symmetry {
+
+    /// All symmetries.
+    pub const ALL: [Symmetry; 7] = [
+        Symmetry::None,
+        Symmetry::Rotational180,
+        Symmetry::Rotational90,
+        Symmetry::HorizontalMirror,
+        Symmetry::VerticalMirror,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ];
+
+    /// Get the squares that are mapped onto each other by this symmetry,
+    /// including the square at (`x` / `y`) itself.
+    ///
+    /// ```
+    /// use sudoku::generator::Symmetry;
+    ///
+    /// assert_eq!(Symmetry::Rotational90.orbit(1, 0), vec![(1, 0), (8, 1), (7, 8), (0, 7)]);
+    ///
+    /// assert_eq!(Symmetry::Rotational180.orbit(4, 4), vec![(4, 4)]);
+    /// ```
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn orbit(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        if x > 8 || y > 8 {
+            panic!("x and y must both be <= 8 (x = {}, y = {})", x, y);
+        }
+
+        let mut orbit = vec![(x, y)];
+        let mut square = self.map(x, y);
+
+        while square != (x, y) {
+            orbit.push(square);
+            square = self.map(square.0, square.1);
+        }
+
+        orbit
+    }
+
+    /// Check if the layout of the clues of `sudoku` has this symmetry.
+    pub fn is_symmetric(&self, sudoku: &Sudoku) -> bool {
+        (0..NUM_SQUARES).all(|index| {
+            let (x, y) = (index % 9, index / 9);
+            let (mapped_x, mapped_y) = self.map(x, y);
+
+            (sudoku.get_value(x, y) == 0) == (sudoku.get_value(mapped_x, mapped_y) == 0)
+        })
+    }
+
+    /// Map a square to its counterpart.
+    fn map(&self, x: usize, y: usize) -> (usize, usize) {
+        match self {
+            Symmetry::None => (x, y),
+            Symmetry::Rotational180 => (8 - x, 8 - y),
+            Symmetry::Rotational90 => (8 - y, x),
+            Symmetry::HorizontalMirror => (x, 8 - y),
+            Symmetry::VerticalMirror => (8 - x, y),
+            Symmetry::Diagonal => (y, x),
+            Symmetry::AntiDiagonal => (8 - y, 8 - x),
+        }
+    }
+
+    /// Get all orbits of this symmetry, each square is in exactly one of them.
+    fn orbits(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut covered = [false; NUM_SQUARES];
+        let mut orbits = Vec::new();
+
+        for index in 0..NUM_SQUARES {
+            if covered[index] {
+                continue;
+            }
+
+            let orbit = self.orbit(index % 9, index / 9);
+            for &(x, y) in &orbit {
+                covered[x + y * 9] = true;
+            }
+            orbits.push(orbit);
+        }
+
+        orbits
+    }
+}
+
+/// Fill the empty squares of `grid`, starting at index `start`, with random
+/// values such that no value appears twice in any row, column or box.
+///
+/// Return `false` if the grid cannot be filled.
+fn fill_randomly(grid: &mut [u32; NUM_SQUARES], start: usize, rng: &mut StdRng) -> bool {
+    let index = match (start..NUM_SQUARES).find(|&index| grid[index] == 0) {
+        Some(index) => index,
+        None => return true,
+    };
+
+    let (x, y) = (index % 9, index / 9);
+
+    let mut values = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+    values.shuffle(rng);
+
+    for value in values {
+        if can_place(grid, x, y, value) {
+            grid[index] = value;
+            if fill_randomly(grid, index + 1, rng) {
+                return true;
+            }
+        }
+    }
+
+    grid[index] = 0;
+
+    false
+}
+
+/// Check if `value` can be placed at (`x` / `y`) without appearing twice in a
+/// row, column or box.
+fn can_place(grid: &[u32; NUM_SQUARES], x: usize, y: usize, value: u32) -> bool {
+    let box_x = (x / 3) * 3;
+    let box_y = (y / 3) * 3;
+
+    (0..9).all(|i| {
+        grid[i + y * 9] != value &&
+        grid[x + i * 9] != value &&
+        grid[(box_x + i % 3) + (box_y + i / 3) * 9] != value
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Generator, Symmetry, MIN_CLUES};
+
+    use crate::NUM_SQUARES;
+    use crate::rating::Difficulty;
+
+    #[test]
+    fn generate_solved_is_solved() {
+        let mut generator = Generator::with_seed(0);
+
+        for _ in 0..10 {
+            assert!(generator.generate_solved().is_solved());
+        }
+    }
+
+    #[test]
+    fn generate_has_unique_solution() {
+        let mut generator = Generator::with_seed(3).target_clues(28);
+
+        for _ in 0..3 {
+            let puzzle = generator.generate();
+
+            assert!(puzzle.is_valid());
+            assert!(puzzle.has_unique_solution());
+        }
+    }
+
+    #[test]
+    fn generate_is_minimal_without_target() {
+        let puzzle = Generator::with_seed(11).generate();
+
+        // removing any further clue must lead to multiple solutions
+        for y in 0..9 {
+            for x in 0..9 {
+                if puzzle.get_value(x, y) != 0 {
+                    let mut reduced = puzzle;
+                    reduced.set_value(x, y, 0);
+                    assert!(!reduced.has_unique_solution());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_reaches_target_clues() {
+        let mut generator = Generator::with_seed(11).target_clues(30);
+
+        for _ in 0..3 {
+            assert_eq!(NUM_SQUARES - generator.generate().num_empty_squares(), 30);
+        }
+    }
+
+    #[test]
+    fn generate_same_seed_same_puzzle() {
+        let mut generator_0 = Generator::with_seed(1234).target_clues(35);
+        let mut generator_1 = Generator::with_seed(1234).target_clues(35);
+
+        for _ in 0..3 {
+            assert_eq!(generator_0.generate(), generator_1.generate());
+        }
+    }
+
+    #[test]
+    fn generate_symmetric() {
+        for symmetry in Symmetry::ALL {
+            let mut generator = Generator::with_seed(21).symmetry(symmetry);
+
+            for _ in 0..2 {
+                let puzzle = generator.generate();
+
+                assert!(symmetry.is_symmetric(&puzzle), "{:?}", symmetry);
+                assert!(puzzle.has_unique_solution());
+            }
+        }
+    }
+
+    #[test]
+    fn generate_symmetric_respects_target_clues() {
+        let puzzle = Generator::with_seed(8).symmetry(Symmetry::Rotational90).target_clues(36).generate();
+
+        assert!(Symmetry::Rotational90.is_symmetric(&puzzle));
+        // orbits have 4 squares, except for the center
+        assert!((36..40).contains(&(NUM_SQUARES - puzzle.num_empty_squares())));
+    }
+
+    #[test]
+    fn generate_with_difficulty_within_band() {
+        let bands = [
+            Difficulty::Easy..=Difficulty::Easy,
+            Difficulty::Medium..=Difficulty::Medium,
+            Difficulty::Hard..=Difficulty::Expert,
+        ];
+
+        for band in bands {
+            let mut generator = Generator::with_seed(17).symmetry(Symmetry::Rotational180);
+
+            let generated = generator.generate_with_difficulty(band.clone()).unwrap();
+
+            assert!(band.contains(&generated.rating.difficulty));
+            assert!(Symmetry::Rotational180.is_symmetric(&generated.puzzle));
+            assert!(generated.puzzle.has_unique_solution());
+        }
+    }
+
+    #[test]
+    fn generate_with_difficulty_gives_up() {
+        let mut generator = Generator::with_seed(17).max_attempts(0);
+
+        assert_eq!(generator.generate_with_difficulty(..), None);
+    }
+
+    #[test]
+    fn is_above() {
+        assert!(super::is_above(&(Difficulty::Easy..Difficulty::Hard), Difficulty::Hard));
+        assert!(!super::is_above(&(Difficulty::Easy..=Difficulty::Hard), Difficulty::Hard));
+        assert!(!super::is_above(&(Difficulty::Hard..), Difficulty::RequiresGuessing));
+    }
+
+    #[test]
+    fn orbits_partition_grid() {
+        for symmetry in Symmetry::ALL {
+            let num_squares: usize = symmetry.orbits().iter().map(|orbit| orbit.len()).sum();
+
+            assert_eq!(num_squares, NUM_SQUARES);
+        }
+    }
+
+    #[test]
+    fn target_clues_is_clamped() {
+        let puzzle = Generator::with_seed(5).target_clues(100).generate();
+        assert_eq!(puzzle.num_empty_squares(), 0);
+
+        let generator = Generator::with_seed(5).target_clues(3);
+        assert_eq!(generator.target_clues, MIN_CLUES);
+
+        assert!(NUM_SQUARES - puzzle.num_empty_squares() >= MIN_CLUES);
+    }
+}
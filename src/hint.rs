@@ -0,0 +1,189 @@
+//! Hints that reveal the next logical step of a [Sudoku] bit by bit.
+//!
+//! [Sudoku::hint()] returns the simplest next [SolveStep] right away. A
+//! [Hint] wraps such a step and reveals it in stages instead, so a player can
+//! ask for just as much help as needed: first the region to look at, then the
+//! [Technique] to apply there and finally the answer.
+//!
+//! ```
+//! use sudoku::{House, Sudoku};
+//! use sudoku::hint::HintStage;
+//! use sudoku::logic::{Placement, Technique};
+//!
+//! // Values generated with http://www.opensky.ca/sudoku
+//! let sudoku: Sudoku = "7.6.......2...961....65...39..4352..8...9...5..3128..44...82....683...4.......5.1".parse().unwrap();
+//!
+//! let mut hint = sudoku.staged_hint().expect("a technique applies");
+//! assert_eq!(hint.stage(), HintStage::Region);
+//! assert_eq!(hint.region(), vec![House::Box(1)]);
+//! assert_eq!(hint.technique(), None);
+//!
+//! hint.reveal();
+//! assert_eq!(hint.technique(), Some(Technique::HiddenSingle));
+//! assert!(hint.step().is_none());
+//!
+//! hint.reveal();
+//! let step = hint.step().expect("the answer is revealed");
+//! assert_eq!(step.placements, vec![Placement { x: 3, y: 0, value: 2 }]);
+//! ```
+//!
+//! [Sudoku]: crate::Sudoku
+//! [Sudoku::hint()]: crate::Sudoku::hint()
+
+use crate::House;
+use crate::logic::{SolveStep, Technique};
+
+/// How much of a [Hint] is revealed.
+///
+/// The stages are declared in the order in which they are revealed.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum HintStage {
+    /// Only the region to look at is revealed, see [Hint::region()].
+    Region,
+    /// The technique to apply is revealed as well, see [Hint::technique()].
+    Technique,
+    /// The whole step is revealed, see [Hint::step()].
+    Answer,
+}
+
+/// A [SolveStep] that is revealed in stages, see [HintStage].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hint {
+    step: SolveStep,
+    stage: HintStage,
+}
+
+impl Hint {
+
+    /// Initialize a new [Hint] for `step`, revealing only its region.
+    pub fn new(step: SolveStep) -> Hint {
+        Hint {
+            step,
+            stage: HintStage::Region,
+        }
+    }
+
+    /// Get how much of this [Hint] is revealed.
+    pub fn stage(&self) -> HintStage {
+        self.stage
+    }
+
+    /// Reveal the next stage.
+    ///
+    /// Return `false` if the answer was already revealed.
+    pub fn reveal(&mut self) -> bool {
+        self.stage = match self.stage {
+            HintStage::Region => HintStage::Technique,
+            HintStage::Technique => HintStage::Answer,
+            HintStage::Answer => return false,
+        };
+
+        true
+    }
+
+    /// Reveal everything up to and including `stage`. Stages that are already
+    /// revealed stay revealed.
+    pub fn reveal_up_to(&mut self, stage: HintStage) {
+        self.stage = self.stage.max(stage);
+    }
+
+    /// Get the houses to look at, which is revealed in every stage.
+    ///
+    /// These are the houses the technique was applied to (see
+    /// [SolveStep::houses]). Techniques that are not bound to houses, like
+    /// naked singles or wings, point to the boxes containing their pattern
+    /// instead.
+    pub fn region(&self) -> Vec<House> {
+        if !self.step.houses.is_empty() {
+            return self.step.houses.clone();
+        }
+
+        let mut boxes = Vec::new();
+        for &(x, y) in &self.step.squares {
+            let house = House::box_containing(x, y);
+            if !boxes.contains(&house) {
+                boxes.push(house);
+            }
+        }
+
+        boxes
+    }
+
+    /// Get the technique to apply, or `None` if it is not revealed yet.
+    pub fn technique(&self) -> Option<Technique> {
+        if self.stage < HintStage::Technique {
+            return None;
+        }
+
+        Some(self.step.technique)
+    }
+
+    /// Get the whole step with its focus squares, placements and
+    /// eliminations, or `None` if the answer is not revealed yet.
+    pub fn step(&self) -> Option<&SolveStep> {
+        if self.stage < HintStage::Answer {
+            return None;
+        }
+
+        Some(&self.step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::House;
+    use crate::logic::{Placement, SolveStep, Technique};
+
+    use super::{Hint, HintStage};
+
+    fn naked_single() -> SolveStep {
+        SolveStep {
+            technique: Technique::NakedSingle,
+            houses: vec![],
+            squares: vec![(4, 7)],
+            values: vec![2],
+            placements: vec![Placement { x: 4, y: 7, value: 2 }],
+            eliminations: vec![],
+        }
+    }
+
+    #[test]
+    fn stages() {
+        let mut hint = Hint::new(naked_single());
+        assert_eq!(hint.region(), vec![House::Box(7)]);
+        assert_eq!(hint.technique(), None);
+        assert_eq!(hint.step(), None);
+
+        assert!(hint.reveal());
+        assert_eq!(hint.stage(), HintStage::Technique);
+        assert_eq!(hint.technique(), Some(Technique::NakedSingle));
+        assert_eq!(hint.step(), None);
+
+        assert!(hint.reveal());
+        assert!(!hint.reveal());
+        assert_eq!(hint.stage(), HintStage::Answer);
+        assert_eq!(hint.step(), Some(&naked_single()));
+        assert_eq!(hint.region(), vec![House::Box(7)]);
+    }
+
+    #[test]
+    fn reveal_up_to() {
+        let mut hint = Hint::new(naked_single());
+
+        hint.reveal_up_to(HintStage::Answer);
+        assert!(hint.step().is_some());
+
+        hint.reveal_up_to(HintStage::Region);
+        assert_eq!(hint.stage(), HintStage::Answer);
+    }
+
+    #[test]
+    fn region_of_wing() {
+        let mut step = naked_single();
+        step.technique = Technique::XYWing;
+        step.squares = vec![(0, 0), (1, 5), (2, 1)];
+
+        assert_eq!(Hint::new(step).region(), vec![House::Box(0), House::Box(3)]);
+    }
+}
@@ -0,0 +1,384 @@
+//! Undo and redo for edits of a [Sudoku] grid and its pencil marks.
+//!
+//! The [History] is a tree rather than a stack: making a new edit after
+//! undoing some does not throw the undone edits away, it starts a new branch
+//! next to them. [History::redo()] follows the branch that was visited last,
+//! the other branches can be reached with [History::redo_branch()].
+//!
+//! [Game](crate::game::Game) records all its edits in a [History], but the
+//! [History] can also be used on its own, e.g. by a front end working with a
+//! plain [Sudoku]:
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::history::{Change, Edit, History};
+//!
+//! let mut sudoku = Sudoku::new_empty();
+//! let mut history = History::new();
+//!
+//! sudoku.set_value(4, 4, 7);
+//! history.record(Edit::new(vec![Change::Value { x: 4, y: 4, before: 0, after: 7 }]));
+//!
+//! let edit = history.undo().expect("there is an edit to undo");
+//! edit.revert(&mut sudoku);
+//! assert_eq!(sudoku.get_value(4, 4), 0);
+//!
+//! let edit = history.redo().expect("there is an edit to redo");
+//! edit.apply(&mut sudoku);
+//! assert_eq!(sudoku.get_value(4, 4), 7);
+//! ```
+
+use crate::Sudoku;
+
+/// A single change of one square.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Change {
+    /// The value of the square at (`x` / `y`) changed from `before` to
+    /// `after`, where 0 means empty.
+    Value {
+        /// The x coordinate of the square.
+        x: usize,
+        /// The y coordinate of the square.
+        y: usize,
+        /// The value before the change.
+        before: u32,
+        /// The value after the change.
+        after: u32,
+    },
+    /// The pencil mark for `value` in the square at (`x` / `y`) was added
+    /// if `marked` is `true` and removed otherwise.
+    PencilMark {
+        /// The x coordinate of the square.
+        x: usize,
+        /// The y coordinate of the square.
+        y: usize,
+        /// The pencil marked value.
+        value: u32,
+        /// Whether the pencil mark is set after the change.
+        marked: bool,
+    },
+    /// The square at (`x` / `y`) started to count as pencil marked if
+    /// `marked` is `true`, or went back to never being pencil marked
+    /// otherwise, see [PencilMarks::is_marked()].
+    ///
+    /// [PencilMarks::is_marked()]: crate::pencil_marks::PencilMarks::is_marked()
+    PencilMarked {
+        /// The x coordinate of the square.
+        x: usize,
+        /// The y coordinate of the square.
+        y: usize,
+        /// Whether the square counts as pencil marked after the change.
+        marked: bool,
+    },
+}
+
+impl Change {
+
+    /// Get the [Change] that undoes this one.
+    ///
+    /// ```
+    /// use sudoku::history::Change;
+    ///
+    /// let change = Change::Value { x: 1, y: 2, before: 0, after: 5 };
+    ///
+    /// assert_eq!(change.inverse(), Change::Value { x: 1, y: 2, before: 5, after: 0 });
+    /// ```
+    pub fn inverse(&self) -> Change {
+        match *self {
+            Change::Value { x, y, before, after } => Change::Value { x, y, before: after, after: before },
+            Change::PencilMark { x, y, value, marked } => Change::PencilMark { x, y, value, marked: !marked },
+            Change::PencilMarked { x, y, marked } => Change::PencilMarked { x, y, marked: !marked },
+        }
+    }
+}
+
+/// All changes made by one operation, which are undone and redone together.
+///
+/// E.g. filling in all pencil marks at once is a single [Edit] consisting of
+/// many [Change]s.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Edit {
+    changes: Vec<Change>,
+}
+
+impl Edit {
+
+    /// Initialize a new [Edit] from `changes`, in the order in which they are
+    /// applied.
+    pub fn new(changes: Vec<Change>) -> Edit {
+        Edit {
+            changes,
+        }
+    }
+
+    /// Get the changes of this [Edit], in the order in which they are
+    /// applied.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Check if this [Edit] does not change anything.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Apply all [Change::Value]s of this [Edit] to `sudoku`.
+    ///
+    /// Pencil marks are not part of a [Sudoku], so [Change::PencilMark]s are
+    /// skipped.
+    ///
+    /// Panics if the coordinates or a value are out of bounds.
+    pub fn apply(&self, sudoku: &mut Sudoku) {
+        for change in &self.changes {
+            if let Change::Value { x, y, after, .. } = *change {
+                sudoku.set_value(x, y, after);
+            }
+        }
+    }
+
+    /// Undo all [Change::Value]s of this [Edit] on `sudoku`, in reverse
+    /// order.
+    ///
+    /// Panics if the coordinates or a value are out of bounds.
+    pub fn revert(&self, sudoku: &mut Sudoku) {
+        for change in self.changes.iter().rev() {
+            if let Change::Value { x, y, before, .. } = *change {
+                sudoku.set_value(x, y, before);
+            }
+        }
+    }
+}
+
+/// A node of the history tree. The root has no parent and no edit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Node {
+    parent: usize,
+    /// The edit leading from the state of `parent` to the state of this node.
+    edit: Edit,
+    children: Vec<usize>,
+    /// The child [History::redo()] continues with, the one visited last.
+    redo_child: Option<usize>,
+}
+
+/// The index of the root node, the state before the first edit.
+const ROOT: usize = 0;
+
+/// A tree of [Edit]s supporting undo and redo with branches.
+///
+/// Every node of the tree is a state, the edges are the edits between those
+/// states. The [History] only stores the edits, it is up to the caller to
+/// apply or revert the edits it returns.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct History {
+    nodes: Vec<Node>,
+    current: usize,
+}
+
+impl History {
+
+    /// Initialize a new, empty [History].
+    pub fn new() -> History {
+        History {
+            nodes: vec![Node {
+                parent: ROOT,
+                edit: Edit::default(),
+                children: Vec::new(),
+                redo_child: None,
+            }],
+            current: ROOT,
+        }
+    }
+
+    /// Record an edit that was made in the current state.
+    ///
+    /// The edit starts a new branch, so the edits that could be redone
+    /// before can still be reached with [History::redo_branch()]. Empty edits
+    /// are not recorded.
+    pub fn record(&mut self, edit: Edit) {
+        if edit.is_empty() {
+            return;
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            parent: self.current,
+            edit,
+            children: Vec::new(),
+            redo_child: None,
+        });
+
+        let current = &mut self.nodes[self.current];
+        current.children.push(node);
+        current.redo_child = Some(node);
+        self.current = node;
+    }
+
+    /// Check if there is an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        self.current != ROOT
+    }
+
+    /// Check if there is an edit to redo.
+    pub fn can_redo(&self) -> bool {
+        self.nodes[self.current].redo_child.is_some()
+    }
+
+    /// Go back to the state before the last edit.
+    ///
+    /// Return the edit, which has to be reverted by the caller, or `None` if
+    /// there is nothing to undo.
+    pub fn undo(&mut self) -> Option<&Edit> {
+        if !self.can_undo() {
+            return None;
+        }
+
+        let node = self.current;
+        self.current = self.nodes[node].parent;
+
+        Some(&self.nodes[node].edit)
+    }
+
+    /// Redo the edit that was undone last from the current state.
+    ///
+    /// Return the edit, which has to be applied by the caller, or `None` if
+    /// there is nothing to redo.
+    pub fn redo(&mut self) -> Option<&Edit> {
+        let node = self.nodes[self.current].redo_child?;
+        self.current = node;
+
+        Some(&self.nodes[node].edit)
+    }
+
+    /// Get the edits that can be redone from the current state, one for each
+    /// branch, from the oldest to the newest branch.
+    ///
+    /// ```
+    /// use sudoku::history::{Change, Edit, History};
+    ///
+    /// let first = Edit::new(vec![Change::Value { x: 0, y: 0, before: 0, after: 1 }]);
+    /// let second = Edit::new(vec![Change::Value { x: 0, y: 0, before: 0, after: 2 }]);
+    ///
+    /// let mut history = History::new();
+    /// history.record(first.clone());
+    /// history.undo();
+    /// history.record(second.clone());
+    /// history.undo();
+    ///
+    /// assert_eq!(history.redo_branches(), vec![&first, &second]);
+    ///
+    /// // redo follows the branch visited last
+    /// assert_eq!(history.redo(), Some(&second));
+    /// history.undo();
+    /// assert_eq!(history.redo_branch(0), Some(&first));
+    /// ```
+    pub fn redo_branches(&self) -> Vec<&Edit> {
+        self.nodes[self.current]
+            .children
+            .iter()
+            .map(|&child| &self.nodes[child].edit)
+            .collect()
+    }
+
+    /// Redo the edit of the given branch, see [History::redo_branches()].
+    ///
+    /// Return the edit, which has to be applied by the caller, or `None` if
+    /// there is no such branch.
+    pub fn redo_branch(&mut self, branch: usize) -> Option<&Edit> {
+        let node = *self.nodes[self.current].children.get(branch)?;
+        self.nodes[self.current].redo_child = Some(node);
+        self.current = node;
+
+        Some(&self.nodes[node].edit)
+    }
+
+    /// Get the edits leading from the initial state to the current state, in
+    /// the order in which they were made.
+    pub fn edits(&self) -> Vec<&Edit> {
+        let mut edits = Vec::new();
+
+        let mut node = self.current;
+        while node != ROOT {
+            edits.push(&self.nodes[node].edit);
+            node = self.nodes[node].parent;
+        }
+
+        edits.reverse();
+        edits
+    }
+}
+
+impl Default for History {
+    fn default() -> History {
+        History::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Change, Edit, History};
+
+    fn edit(x: usize, value: u32) -> Edit {
+        Edit::new(vec![Change::Value { x, y: 0, before: 0, after: value }])
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = History::new();
+        assert!(!history.can_undo());
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), None);
+
+        history.record(edit(0, 1));
+        history.record(edit(1, 2));
+        history.record(Edit::default());
+        assert_eq!(history.edits(), vec![&edit(0, 1), &edit(1, 2)]);
+
+        assert_eq!(history.undo(), Some(&edit(1, 2)));
+        assert_eq!(history.undo(), Some(&edit(0, 1)));
+        assert_eq!(history.undo(), None);
+        assert!(history.can_redo());
+
+        assert_eq!(history.redo(), Some(&edit(0, 1)));
+        assert_eq!(history.redo(), Some(&edit(1, 2)));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn branches() {
+        let mut history = History::new();
+
+        history.record(edit(0, 1));
+        history.record(edit(1, 2));
+        history.undo();
+        history.record(edit(1, 3));
+        assert_eq!(history.edits(), vec![&edit(0, 1), &edit(1, 3)]);
+
+        history.undo();
+        assert_eq!(history.redo_branches(), vec![&edit(1, 2), &edit(1, 3)]);
+        assert_eq!(history.redo_branch(2), None);
+        assert_eq!(history.redo_branch(0), Some(&edit(1, 2)));
+
+        // the branch taken last is remembered
+        history.undo();
+        history.undo();
+        assert_eq!(history.redo(), Some(&edit(0, 1)));
+        assert_eq!(history.redo(), Some(&edit(1, 2)));
+    }
+
+    #[test]
+    fn apply_and_revert() {
+        let mut sudoku = crate::Sudoku::new_empty();
+        let edit = Edit::new(vec![
+            Change::Value { x: 3, y: 3, before: 0, after: 4 },
+            Change::PencilMark { x: 5, y: 5, value: 4, marked: false },
+            Change::Value { x: 3, y: 3, before: 4, after: 6 },
+        ]);
+
+        edit.apply(&mut sudoku);
+        assert_eq!(sudoku.get_value(3, 3), 6);
+
+        edit.revert(&mut sudoku);
+        assert_eq!(sudoku, crate::Sudoku::new_empty());
+    }
+}
@@ -5,21 +5,52 @@
 
 use itertools::Itertools;
 
+mod bitboard;
+pub mod candidates;
+mod dlx;
+pub mod explain;
+pub mod game;
+pub mod generator;
+pub mod hint;
+pub mod history;
+pub mod logic;
+pub mod parallel;
+pub mod path;
+pub mod pencil_marks;
+pub mod rating;
+pub mod solver;
+
+use candidates::Candidates;
+use hint::Hint;
+use logic::{LogicalSolver, SolveStep};
+use path::SolutionPath;
+use rating::Rating;
+use solver::Solver;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt;
+use std::str::FromStr;
+
 /// The number of squares on a Sudoku grid.
 pub const NUM_SQUARES: usize = 9 * 9;
 
-/// Panic if either coordinate is >= 9.
-fn validate_coordinates(x: usize, y: usize) {
+/// Return an error if either coordinate is >= 9.
+fn validate_coordinates(x: usize, y: usize) -> Result<(), SudokuError> {
     if x > 8 || y > 8 {
-        panic!("x and y must both be <= 8 (x = {}, y = {})", x, y);
+        return Err(SudokuError::InvalidCoordinates { x, y });
     }
+
+    Ok(())
 }
 
-/// Panic if the value is > 9.
-fn validate_value(value: u32) {
+/// Return an error if the value is > 9.
+fn validate_value(value: u32) -> Result<(), SudokuError> {
     if value > 9 {
-        panic!("Value must be <= 9 (was {})", value );
+        return Err(SudokuError::InvalidValue(value));
     }
+
+    Ok(())
 }
 
 /// A Sudoku grid
@@ -34,10 +65,12 @@ fn validate_value(value: u32) {
 ///
 /// This implementation guarantees that values cannot be bigger than 9 and
 /// panics if supplied with any. It also panics if invalid coordinates are
-/// supplied.
+/// supplied. If panicking is not an option, use the fallible counterparts
+/// [Sudoku::try_get_value()], [Sudoku::try_set_value()] and the [TryFrom]
+/// implementations, which return a [SudokuError] instead.
 // TODO the derived Debug implementation is very ugly, maybe manually implement
 // it
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Sudoku {
     grid: [u32; 81],
 }
@@ -85,14 +118,11 @@ impl Sudoku {
     ///
     /// assert_eq!(sudoku_array[i], sudoku.get_value(x, y));
     /// ```
+    ///
+    /// Panics if any of the values is invalid. See the [TryFrom]
+    /// implementation for a version of this function that does not panic.
     pub fn new_from_array(array: [u32; NUM_SQUARES]) -> Sudoku {
-        for value in array {
-            validate_value(value);
-        }
-
-        Sudoku {
-            grid: array,
-        }
+        Sudoku::try_from(array).unwrap_or_else(|error| panic!("{}", error))
     }
 
     /// Initialize a new empty Sudoku board.
@@ -122,6 +152,77 @@ impl Sudoku {
         }
     }
 
+    /// Parse a [Sudoku] from the common single line format.
+    ///
+    /// The line has to contain exactly 81 squares, listed row by row
+    /// starting in the top left corner. A square is either a digit from `1` to
+    /// `9` or an empty square, which can be written as any of `0`, `.`, `_` or
+    /// `*`. Whitespace is ignored, so the grid may also be split over multiple
+    /// lines (the output of [Sudoku::string_repr()] can be parsed as well).
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    ///
+    /// // Values generated with http://www.opensky.ca/sudoku
+    /// let sudoku = Sudoku::parse_line("43...98..19.8....5...724....61.9..2...........2..5.69....637...8....5.31..51...76")
+    ///     .expect("this is a valid line");
+    ///
+    /// assert_eq!(sudoku, Sudoku::new_from_array([4, 3, 0, 0, 0, 9, 8, 0, 0,
+    ///                                            1, 9, 0, 8, 0, 0, 0, 0, 5,
+    ///                                            0, 0, 0, 7, 2, 4, 0, 0, 0,
+    ///                                            0, 6, 1, 0, 9, 0, 0, 2, 0,
+    ///                                            0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ///                                            0, 2, 0, 0, 5, 0, 6, 9, 0,
+    ///                                            0, 0, 0, 6, 3, 7, 0, 0, 0,
+    ///                                            8, 0, 0, 0, 0, 5, 0, 3, 1,
+    ///                                            0, 0, 5, 1, 0, 0, 0, 7, 6]));
+    /// ```
+    ///
+    /// Instead of panicking, a [ParseSudokuError] describing what is wrong
+    /// with the line is returned if it cannot be parsed.
+    ///
+    /// ```
+    /// use sudoku::{ParseSudokuError, Sudoku};
+    ///
+    /// let error = Sudoku::parse_line("12x").unwrap_err();
+    ///
+    /// assert_eq!(error, ParseSudokuError::InvalidCharacter { position: 2, character: 'x' });
+    /// ```
+    ///
+    /// [Sudoku] also implements [std::str::FromStr], so `line.parse::<Sudoku>()`
+    /// does the same thing.
+    pub fn parse_line(line: &str) -> Result<Sudoku, ParseSudokuError> {
+        let mut grid = [0; NUM_SQUARES];
+        let mut num_squares = 0;
+
+        for (position, character) in line.chars().enumerate() {
+            if character.is_whitespace() {
+                continue;
+            }
+
+            let value = match character {
+                '0' | '.' | '_' | '*' => 0,
+                '1'..='9' => character.to_digit(10).expect("character is a digit"),
+                _ => return Err(ParseSudokuError::InvalidCharacter { position, character }),
+            };
+
+            if num_squares == NUM_SQUARES {
+                return Err(ParseSudokuError::TooManySquares { position });
+            }
+
+            grid[num_squares] = value;
+            num_squares += 1;
+        }
+
+        if num_squares < NUM_SQUARES {
+            return Err(ParseSudokuError::TooFewSquares(num_squares));
+        }
+
+        Ok(Sudoku {
+            grid,
+        })
+    }
+
     /// Get the value at the given coordinates in the Sudoku grid.
     ///
     /// ```
@@ -146,21 +247,69 @@ impl Sudoku {
     /// assert_eq!(sudoku.get_value(3, 5), 3);
     /// ```
     ///
-    /// Panics if the coordinates are out of bounds.
+    /// Panics if the coordinates are out of bounds. See
+    /// [Sudoku::try_get_value()] for a version of this function that does not
+    /// panic.
     pub fn get_value(&self, x: usize, y: usize) -> u32 {
-        validate_coordinates(x, y);
+        self.try_get_value(x, y).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Get the value at the given coordinates in the Sudoku grid.
+    ///
+    /// Return [SudokuError::InvalidCoordinates] if the coordinates are out of
+    /// bounds.
+    ///
+    /// ```
+    /// use sudoku::{Sudoku, SudokuError};
+    ///
+    /// let sudoku = Sudoku::new_empty();
+    ///
+    /// assert_eq!(sudoku.try_get_value(4, 2), Ok(0));
+    ///
+    /// assert_eq!(sudoku.try_get_value(4, 9), Err(SudokuError::InvalidCoordinates { x: 4, y: 9 }));
+    /// ```
+    pub fn try_get_value(&self, x: usize, y: usize) -> Result<u32, SudokuError> {
+        validate_coordinates(x, y)?;
 
-        self.grid[x + y * 9]
+        Ok(self.grid[x + y * 9])
     }
 
     /// Set the value at the given coordinates in the Sudoku grid.
     ///
-    /// Pancis if the coordinates are out of bounds or if `value` is invalid.
+    /// Panics if the coordinates are out of bounds or if `value` is invalid.
+    /// See [Sudoku::try_set_value()] for a version of this function that does
+    /// not panic.
     pub fn set_value(&mut self, x: usize, y: usize, value: u32) {
-        validate_coordinates(x, y);
-        validate_value(value);
+        self.try_set_value(x, y, value).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Set the value at the given coordinates in the Sudoku grid.
+    ///
+    /// Return [SudokuError::InvalidCoordinates] if the coordinates are out of
+    /// bounds and [SudokuError::InvalidValue] if `value` is invalid. The grid
+    /// is left untouched in both cases.
+    ///
+    /// ```
+    /// use sudoku::{Sudoku, SudokuError};
+    ///
+    /// let mut sudoku = Sudoku::new_empty();
+    ///
+    /// assert_eq!(sudoku.try_set_value(2, 5, 7), Ok(()));
+    /// assert_eq!(sudoku.get_value(2, 5), 7);
+    ///
+    /// assert_eq!(sudoku.try_set_value(2, 5, 10), Err(SudokuError::InvalidValue(10)));
+    /// assert_eq!(sudoku.get_value(2, 5), 7);
+    /// ```
+    ///
+    /// Note that this does not check whether the value conflicts with other
+    /// values on the grid, see [Sudoku::validate()] for that.
+    pub fn try_set_value(&mut self, x: usize, y: usize, value: u32) -> Result<(), SudokuError> {
+        validate_coordinates(x, y)?;
+        validate_value(value)?;
 
         self.grid[x + y * 9] = value;
+
+        Ok(())
     }
 
     /// Check if this [Sudoku] is solved.
@@ -258,8 +407,13 @@ impl Sudoku {
     /// the return value of this function unless you are certain that the number of
     /// possible solutions is very limited. Otherwise you'll likely get stuck in an
     /// almost infinite loop.
-    pub fn find_all_solutions(&self) -> impl Iterator<Item = Sudoku> + '_ {
-        AllSolutionsIterator::new(self)
+    ///
+    /// The returned [solver::Solutions] owns a copy of the [Sudoku], so it can
+    /// be stored anywhere or sent to another thread.
+    ///
+    /// To use another [solver::Engine], see [Solver::find_all_solutions()].
+    pub fn find_all_solutions(&self) -> solver::Solutions {
+        Solver::new().find_all_solutions(self)
     }
 
     /// Return `true` if this [Sudoku] is solvable.
@@ -306,6 +460,128 @@ impl Sudoku {
         self.find_solution().is_some()
     }
 
+    /// Count the solutions of this [Sudoku], but stop as soon as `limit`
+    /// solutions have been found.
+    ///
+    /// ```
+    /// use sudoku::{SolutionCount, Sudoku};
+    ///
+    /// // taken from https://puzzling.stackexchange.com/questions/67789/examples-of-sudokus-with-two-solutions
+    /// let sudoku: Sudoku = "2957438614318659..8761925433874592166123874955492167387635241899286713541549386..".parse().unwrap();
+    ///
+    /// assert_eq!(sudoku.count_solutions(10), SolutionCount::Multiple(2));
+    ///
+    /// // An empty grid has billions of solutions, but counting stops early
+    /// assert_eq!(Sudoku::new_empty().count_solutions(100), SolutionCount::Multiple(100));
+    /// ```
+    ///
+    /// A result of `SolutionCount::Multiple(limit)` means that there are *at
+    /// least* `limit` solutions. A `limit` smaller than 2 is treated like 2,
+    /// because otherwise a unique solution could not be told apart from
+    /// multiple solutions.
+    pub fn count_solutions(&self, limit: usize) -> SolutionCount {
+        Solver::new().count_solutions(self, limit)
+    }
+
+    /// Return `true` if this [Sudoku] has exactly one solution.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    ///
+    /// // taken from https://puzzling.stackexchange.com/questions/67789/examples-of-sudokus-with-two-solutions
+    /// let mut sudoku: Sudoku = "2957438614318659..8761925433874592166123874955492167387635241899286713541549386..".parse().unwrap();
+    ///
+    /// // 2 and 7 can go into the last two squares of the second row either way
+    /// assert!(!sudoku.has_unique_solution());
+    ///
+    /// sudoku.set_value(7, 1, 2);
+    /// assert!(sudoku.has_unique_solution());
+    /// ```
+    ///
+    /// This stops searching as soon as a second solution is found, see
+    /// [Sudoku::count_solutions()].
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == SolutionCount::Unique
+    }
+
+    /// Rate the difficulty of this [Sudoku] puzzle.
+    ///
+    /// The puzzle is solved with the [logic::LogicalSolver] and graded by the
+    /// hardest technique that was needed. If the solver gets stuck before the
+    /// puzzle is solved, it is graded as
+    /// [rating::Difficulty::RequiresGuessing]. This is also the case for
+    /// puzzles with more than one solution.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::rating::Difficulty;
+    ///
+    /// // Values generated with http://www.opensky.ca/sudoku
+    /// let sudoku = Sudoku::new_from_array([0, 0, 1, 0, 2, 0, 9, 0, 0,
+    ///                                      9, 0, 0, 0, 4, 0, 0, 2, 0,
+    ///                                      0, 2, 0, 0, 9, 8, 0, 5, 1,
+    ///                                      0, 1, 7, 0, 0, 0, 0, 0, 0,
+    ///                                      4, 0, 0, 7, 0, 6, 0, 0, 9,
+    ///                                      0, 0, 0, 0, 0, 0, 6, 1, 0,
+    ///                                      1, 3, 0, 8, 7, 0, 0, 6, 0,
+    ///                                      0, 7, 0, 0, 5, 0, 0, 0, 4,
+    ///                                      0, 0, 5, 0, 6, 0, 3, 0, 0]);
+    ///
+    /// let rating = sudoku.rate().expect("the puzzle is solvable");
+    ///
+    /// assert_eq!(rating.difficulty, Difficulty::Medium);
+    /// ```
+    ///
+    /// Return `None` if the puzzle has no solution.
+    pub fn rate(&self) -> Option<Rating> {
+        rating::rate(self)
+    }
+
+    /// Get the simplest next logical step, which is a hint at what to do next
+    /// without giving away the whole solution.
+    ///
+    /// The step is found by the [LogicalSolver], which tries its techniques in
+    /// the order of [logic::Technique::ALL], starting with the candidates
+    /// from [Sudoku::candidates()].
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::logic::{Placement, Technique};
+    ///
+    /// // Generated with https://sudokukingdom.com/very-easy-sudoku.php
+    /// let sudoku: Sudoku = "7.6.5.419..9.748..48.6.9..5..89.5.34942.6.1..3..4.1.92.9...658153.14..2..6159.3..".parse().unwrap();
+    ///
+    /// let step = sudoku.hint().expect("a technique applies");
+    ///
+    /// assert_eq!(step.technique.name(), "Hidden Single");
+    /// assert_eq!(step.squares, vec![(2, 2)]);
+    /// assert_eq!(step.placements, vec![Placement { x: 2, y: 2, value: 3 }]);
+    /// ```
+    ///
+    /// Return `None` if the [Sudoku] is solved, if it has a contradiction (see
+    /// [LogicalSolver::has_contradiction()]) or if none of the techniques of
+    /// the [LogicalSolver] applies.
+    pub fn hint(&self) -> Option<SolveStep> {
+        LogicalSolver::new(self).next_step()
+    }
+
+    /// Get the simplest next logical step as a [Hint] that reveals only the
+    /// region to look at at first, see [hint].
+    ///
+    /// Return `None` in the same cases as [Sudoku::hint()].
+    pub fn staged_hint(&self) -> Option<Hint> {
+        self.hint().map(Hint::new)
+    }
+
+    /// Solve this [Sudoku] with the [LogicalSolver] and record every step
+    /// along with the grid and the candidates after it, see [path].
+    ///
+    /// If the [LogicalSolver] gets stuck, the path ends there, which can be
+    /// checked with [SolutionPath::is_solved()].
+    pub fn solution_path(&self) -> SolutionPath {
+        SolutionPath::new(self)
+    }
+
     /// Check if this [Sudoku] is valid.
     ///
     /// A [Sudoku] is considered valid if it contains no duplicate values
@@ -340,6 +616,47 @@ impl Sudoku {
         self.fulfills_in_3x3_cell_condition()
     }
 
+    /// Check if this [Sudoku] is valid and report the first conflict found.
+    ///
+    /// This does the same check as [Sudoku::is_valid()], but returns a
+    /// [SudokuError::ConflictingGivens] holding the duplicated value and the
+    /// coordinates of the two squares containing it instead of just `false`.
+    ///
+    /// ```
+    /// use sudoku::{Sudoku, SudokuError};
+    ///
+    /// let mut sudoku = Sudoku::new_empty();
+    /// sudoku.set_value(1, 1, 4);
+    /// assert_eq!(sudoku.validate(), Ok(()));
+    ///
+    /// sudoku.set_value(6, 1, 4);
+    /// assert_eq!(sudoku.validate(), Err(SudokuError::ConflictingGivens { value: 4,
+    ///                                                                    first: (1, 1),
+    ///                                                                    second: (6, 1) }));
+    /// ```
+    ///
+    /// Rows are checked first, then columns and then the 3x3 cells.
+    pub fn validate(&self) -> Result<(), SudokuError> {
+        for squares in House::all().map(|house| house.squares()) {
+            // The coordinates of the first square found containing each value
+            let mut first_occurrences = [None; 10];
+
+            for (x, y) in squares {
+                let value = self.grid[x + y * 9];
+                if value == 0 {
+                    continue;
+                }
+
+                match first_occurrences[value as usize] {
+                    Some(first) => return Err(SudokuError::ConflictingGivens { value, first, second: (x, y) }),
+                    None => first_occurrences[value as usize] = Some((x, y)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// True if this [Sudoku] has any empty squares.
     ///
     /// ```
@@ -443,16 +760,11 @@ impl Sudoku {
     /// # assert!(sudoku.is_solvable());
     /// ```
     pub fn num_occurrences_of(&self, value: u32) -> usize {
-        validate_value(value);
+        validate_value(value).unwrap_or_else(|error| panic!("{}", error));
 
         self.grid.iter().filter(|&item| *item == value).count()
     }
 
-    // There is some repeated code in the following three functions
-    // (`fulfills_horizontal_condition()`, `fulfills_vertical_condition()`,
-    // `fulfills_in_3x3_cell_condition`). It would be possible to extract that
-    // code into a new struct. However, I am too lazy to do this...
-
     /// True if this [Sudoku] grid has no duplicate values within any
     /// horizontal line.
     ///
@@ -472,28 +784,7 @@ impl Sudoku {
     /// assert!(sudoku.fulfills_horizontal_condition());
     /// ```
     pub fn fulfills_horizontal_condition(&self) -> bool {
-        for y in 0..9 {
-            let mut bit_flags = 0;
-
-            for x in 0..9 {
-                let value = self.grid[x + y * 9];
-                // Check if the value has already been encountered, if yes, the
-                // condition is not fulfilled. Else set a flag in `bit_flags`
-                // that the value has been encountered.
-                //
-                // Zero's can just be ignored, empty squares don't matter for
-                // this calculation.
-                if value == 0 {
-                    continue;
-                } else if (bit_flags >> (value - 1)) & 1 == 1 {
-                    return false;
-                } else {
-                    bit_flags |= 1 << (value - 1);
-                }
-            }
-        }
-
-        true
+        (0..9).all(|y| self.duplicate_values(House::Row(y)) == 0)
     }
 
     /// True if this [Sudoku] grid has no duplicate values within any vertical
@@ -515,28 +806,7 @@ impl Sudoku {
     /// assert!(sudoku.fulfills_vertical_condition());
     /// ```
     pub fn fulfills_vertical_condition(&self) -> bool {
-         for x in 0..9 {
-            let mut bit_flags = 0;
-
-            for y in 0..9 {
-                let value = self.grid[x + y * 9];
-                // Check if the value has already been encountered, if yes, the
-                // condition is not fulfilled. Else set a flag in `bit_flags`
-                // that the value has been encountered.
-                //
-                // Zero's can just be ignored, empty squares don't matter for
-                // this calculation.
-                if value == 0 {
-                    continue;
-                } else if (bit_flags >> (value - 1)) & 1 == 1 {
-                    return false;
-                } else {
-                    bit_flags |= 1 << (value - 1);
-                }
-            }
-        }
-
-        true
+        (0..9).all(|x| self.duplicate_values(House::Column(x)) == 0)
     }
 
     /// True if this [Sudoku] grid has no duplicate values within any of the 9
@@ -558,38 +828,152 @@ impl Sudoku {
     /// assert!(sudoku.fulfills_in_3x3_cell_condition());
     /// ```
     pub fn fulfills_in_3x3_cell_condition(&self) -> bool {
-        for x_cell in 0..3 {
-            for y_cell in 0..3 {
-                let min_x = x_cell * 3;
-                let max_x = min_x + 2; // inclusive
-
-                let min_y = y_cell * 3;
-                let max_y = min_y + 2; // inclusive
-
-                let mut bit_flags = 0;
-
-                for x in min_x..=max_x {
-                    for y in min_y..=max_y {
-                        let value = self.grid[x + y * 9];
-                        // Check if the value has already been encountered, if yes, the
-                        // condition is not fulfilled. Else set a flag in `bit_flags`
-                        // that the value has been encountered.
-                        //
-                        // Zero's can just be ignored, empty squares don't matter for
-                        // this calculation.
-                        if value == 0 {
-                            continue;
-                        } else if (bit_flags >> (value - 1)) & 1 == 1 {
-                            return false;
-                        } else {
-                            bit_flags |= 1 << (value - 1);
-                        }
-                    }
+        (0..9).all(|index| self.duplicate_values(House::Box(index)) == 0)
+    }
+
+    /// Get every conflict on this [Sudoku] grid.
+    ///
+    /// A [Conflict] is a value that appears more than once in the same row,
+    /// column or 3x3 cell. For every such value and [House], the returned
+    /// [Vec] contains one [Conflict] listing all the squares containing the
+    /// value. A square may therefore be part of up to three conflicts.
+    ///
+    /// ```
+    /// use sudoku::{Conflict, House, Sudoku};
+    ///
+    /// let mut sudoku = Sudoku::new_empty();
+    /// sudoku.set_value(0, 0, 5);
+    /// sudoku.set_value(7, 0, 5);
+    /// sudoku.set_value(2, 2, 5);
+    ///
+    /// assert_eq!(sudoku.conflicts(), vec![
+    ///     Conflict { house: House::Row(0), value: 5, squares: vec![(0, 0), (7, 0)] },
+    ///     Conflict { house: House::Box(0), value: 5, squares: vec![(0, 0), (2, 2)] },
+    /// ]);
+    /// ```
+    ///
+    /// The conflicts in rows come first, then those in columns and then those
+    /// in 3x3 cells (see [House::all()]). An empty [Vec] is returned if and
+    /// only if the [Sudoku] is valid.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+
+        for house in House::all() {
+            let duplicate_values = self.duplicate_values(house);
+            if duplicate_values == 0 {
+                continue;
+            }
+
+            for value in 1..=9 {
+                if (duplicate_values >> (value - 1)) & 1 == 0 {
+                    continue;
                 }
+
+                let squares = house
+                    .squares()
+                    .into_iter()
+                    .filter(|&(x, y)| self.grid[x + y * 9] == value)
+                    .collect();
+
+                conflicts.push(Conflict { house, value, squares });
+            }
+        }
+
+        conflicts
+    }
+
+    /// Get the [Candidates] of every square, the values that do not appear
+    /// in the row, the column or the 3x3 cell of the square yet.
+    ///
+    /// If `i` is an index into the returned array, `candidates[i]` belongs to
+    /// the square at ( `x = i % 9` / `y = i / 9` ), just like in
+    /// [Sudoku::new_from_array()]. Squares that already contain a value have
+    /// no candidates.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::candidates::Candidates;
+    ///
+    /// let mut sudoku = Sudoku::new_empty();
+    /// sudoku.set_value(0, 0, 1);
+    /// sudoku.set_value(8, 1, 2);
+    ///
+    /// let candidates = sudoku.candidates();
+    ///
+    /// assert_eq!(candidates[0], Candidates::new());
+    /// assert_eq!(candidates[1], Candidates::from_iter(2..=9));
+    /// assert_eq!(candidates[4 + 4 * 9], Candidates::all());
+    /// ```
+    ///
+    /// For an invalid [Sudoku], the candidates are computed all the same, so
+    /// an empty square may end up without any candidates.
+    pub fn candidates(&self) -> [Candidates; NUM_SQUARES] {
+        std::array::from_fn(|i| self.square_candidates(i % 9, i / 9))
+    }
+
+    /// Get the [Candidates] of the square at (`x` / `y`), see
+    /// [Sudoku::candidates()].
+    ///
+    /// Only the row, the column and the 3x3 cell of the square are looked
+    /// at, so this is cheaper than [Sudoku::candidates()] for a single
+    /// square.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    ///
+    /// let mut sudoku = Sudoku::new_empty();
+    /// sudoku.set_value(0, 0, 1);
+    /// sudoku.set_value(8, 1, 2);
+    ///
+    /// assert_eq!(sudoku.candidates_at(2, 1).to_string(), "3456789");
+    /// assert!(sudoku.candidates_at(0, 0).is_empty());
+    /// ```
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn candidates_at(&self, x: usize, y: usize) -> Candidates {
+        validate_coordinates(x, y).unwrap_or_else(|error| panic!("{}", error));
+
+        self.square_candidates(x, y)
+    }
+
+    /// Get the [Candidates] of the square at (`x` / `y`), which must be
+    /// valid coordinates.
+    fn square_candidates(&self, x: usize, y: usize) -> Candidates {
+        if self.grid[x + y * 9] != 0 {
+            return Candidates::new();
+        }
+
+        let flags = [House::Row(y), House::Column(x), House::box_containing(x, y)]
+            .into_iter()
+            .fold(SudokuNote::ALL_VALUES_POSSIBLE, |flags, house| flags & missing_values(self, house));
+
+        Candidates::from_bits(flags as u16).expect("only the flags of the values 1 to 9 are ever set")
+    }
+
+    /// Get the values that appear more than once within a [House] as bit
+    /// flags (bit `value - 1` is set if `value` is duplicated).
+    fn duplicate_values(&self, house: House) -> u32 {
+        let mut bit_flags = 0;
+        let mut duplicates = 0;
+
+        for (x, y) in house.squares() {
+            let value = self.grid[x + y * 9];
+            // Check if the value has already been encountered, if yes, it is
+            // a duplicate. Else set a flag in `bit_flags` that the value has
+            // been encountered.
+            //
+            // Zero's can just be ignored, empty squares don't matter for
+            // this calculation.
+            if value == 0 {
+                continue;
+            } else if (bit_flags >> (value - 1)) & 1 == 1 {
+                duplicates |= 1 << (value - 1);
+            } else {
+                bit_flags |= 1 << (value - 1);
             }
         }
 
-        true
+        duplicates
     }
 
     /// Get a [String] representation of this [Sudoku] grid.
@@ -623,37 +1007,398 @@ impl Sudoku {
     /// 0 1 9 0 8 0 0 0 6
     /// ";
     ///
-    /// assert_eq!(sudoku.string_repr(), string_repr);
-    /// #
-    /// # // I want all examples to be solvable
-    /// # assert!(sudoku.is_solvable());
+    /// assert_eq!(sudoku.string_repr(), string_repr);
+    /// #
+    /// # // I want all examples to be solvable
+    /// # assert!(sudoku.is_solvable());
+    /// ```
+    ///
+    /// This is not used for implementing the [Debug] or [std::fmt::Display]
+    /// traits, because, spanning 9 lines, the output is quite bulky, which is
+    /// not practical in every case.
+    // I think this function could be optimized some more (a lot of `String`s
+    // are created), but I don't feel like figuring out how to optimize it
+    // further.
+    #[allow(unstable_name_collisions)]
+    pub fn string_repr(&self) -> String {
+        let mut string_repr = self.grid
+            .iter()
+            .chunks(9)
+            .into_iter()
+            .map(|row| row
+                 .map(|value| value.to_string())
+                 .intersperse(" ".to_owned())
+                 .collect::<String>())
+            .intersperse("\n".to_owned())
+            .collect::<String>();
+
+        string_repr.push('\n');
+
+        string_repr
+    }
+
+    /// Get a one-line string representation of this [Sudoku], the values of
+    /// all squares row by row, 0 for empty squares.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    ///
+    /// let sudoku = Sudoku::new_from_array([5, 0, 0, 0, 9, 0, 3, 8, 0,
+    ///                                      0, 0, 0, 6, 0, 0, 0, 0, 4,
+    ///                                      9, 0, 3, 8, 0, 1, 0, 7, 0,
+    ///                                      2, 8, 0, 0, 5, 0, 0, 0, 0,
+    ///                                      0, 4, 0, 0, 0, 0, 0, 6, 0,
+    ///                                      0, 0, 0, 0, 7, 0, 0, 4, 8,
+    ///                                      0, 5, 0, 7, 0, 4, 8, 0, 9,
+    ///                                      7, 0, 0, 0, 0, 9, 0, 0, 0,
+    ///                                      0, 1, 9, 0, 8, 0, 0, 0, 6]);
+    ///
+    /// let line = "500090380000600004903801070280050000040000060000070048050704809700009000019080006";
+    ///
+    /// assert_eq!(sudoku.line_repr(), line);
+    /// assert_eq!(Sudoku::parse_line(line), Ok(sudoku));
+    /// #
+    /// # // I want all examples to be solvable
+    /// # assert!(sudoku.is_solvable());
+    /// ```
+    ///
+    /// This is the format read by [Sudoku::parse_line()].
+    pub fn line_repr(&self) -> String {
+        self.grid.iter().map(|value| value.to_string()).collect()
+    }
+}
+
+impl FromStr for Sudoku {
+    type Err = ParseSudokuError;
+
+    /// Parse a [Sudoku] from a string. See [Sudoku::parse_line()] for the
+    /// accepted format.
+    fn from_str(s: &str) -> Result<Sudoku, ParseSudokuError> {
+        Sudoku::parse_line(s)
+    }
+}
+
+/// Serialize a value for every square of a grid as a sequence, in the order
+/// of [Sudoku::new_from_array()].
+pub(crate) fn serialize_squares<T: Serialize, S: Serializer>(squares: &[T; NUM_SQUARES], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(squares)
+}
+
+/// Deserialize a value for every square of a grid from a sequence, rejecting
+/// sequences of any other length than 81.
+pub(crate) fn deserialize_squares<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<[T; NUM_SQUARES], D::Error> {
+    let squares = Vec::<T>::deserialize(deserializer)?;
+    let len = squares.len();
+
+    squares
+        .try_into()
+        .map_err(|_| serde::de::Error::invalid_length(len, &"a value for each of the 81 squares"))
+}
+
+/// A [Sudoku] is serialized as a string of 81 digits, 0 for empty squares.
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.line_repr())
+    }
+}
+
+/// A [Sudoku] is deserialized from any string accepted by
+/// [Sudoku::parse_line()].
+impl<'de> Deserialize<'de> for Sudoku {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Sudoku, D::Error> {
+        let line = String::deserialize(deserializer)?;
+
+        Sudoku::parse_line(&line).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The error returned when a [Sudoku] cannot be parsed from a string.
+///
+/// See [Sudoku::parse_line()].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseSudokuError {
+    /// The string contains a character that is neither a digit, a marker for
+    /// an empty square nor whitespace.
+    ///
+    /// `position` is the index of the offending character (counted in
+    /// characters, not bytes).
+    InvalidCharacter {
+        /// The index of the character in the string.
+        position: usize,
+        /// The offending character.
+        character: char,
+    },
+    /// The string contains fewer than 81 squares. Holds the number of squares
+    /// that were found.
+    TooFewSquares(usize),
+    /// The string contains more than 81 squares.
+    TooManySquares {
+        /// The index of the character describing the 82nd square.
+        position: usize,
+    },
+}
+
+impl fmt::Display for ParseSudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSudokuError::InvalidCharacter { position, character } => {
+                write!(f, "invalid character {:?} at position {}", character, position)
+            },
+            ParseSudokuError::TooFewSquares(num_squares) => {
+                write!(f, "expected {} squares, found only {}", NUM_SQUARES, num_squares)
+            },
+            ParseSudokuError::TooManySquares { position } => {
+                write!(f, "expected {} squares, found more (extra square at position {})", NUM_SQUARES, position)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ParseSudokuError {}
+
+impl TryFrom<[u32; NUM_SQUARES]> for Sudoku {
+    type Error = SudokuError;
+
+    /// Initialize a new Sudoku board from an array.
+    ///
+    /// Works exactly like [Sudoku::new_from_array()], except that a
+    /// [SudokuError::InvalidValue] is returned instead of panicking if any of
+    /// the values is invalid.
+    ///
+    /// ```
+    /// use sudoku::{Sudoku, SudokuError, NUM_SQUARES};
+    ///
+    /// let mut array = [0; NUM_SQUARES];
+    /// array[17] = 12;
+    ///
+    /// assert_eq!(Sudoku::try_from(array), Err(SudokuError::InvalidValue(12)));
+    /// ```
+    fn try_from(array: [u32; NUM_SQUARES]) -> Result<Sudoku, SudokuError> {
+        for value in array {
+            validate_value(value)?;
+        }
+
+        Ok(Sudoku {
+            grid: array,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for Sudoku {
+    type Error = SudokuError;
+
+    /// Initialize a new Sudoku board from a slice of values.
+    ///
+    /// The slice is interpreted the same way as the array passed to
+    /// [Sudoku::new_from_array()], so it has to contain exactly 81 values
+    /// ranging from 0 to 9 (these are values, not ASCII digits, see
+    /// [Sudoku::parse_line()] for parsing text).
+    ///
+    /// ```
+    /// use sudoku::{Sudoku, SudokuError, NUM_SQUARES};
+    ///
+    /// let values = vec![0; NUM_SQUARES];
+    /// assert_eq!(Sudoku::try_from(values.as_slice()), Ok(Sudoku::new_empty()));
+    ///
+    /// assert_eq!(Sudoku::try_from(&values[1..]), Err(SudokuError::InvalidLength(80)));
+    /// ```
+    fn try_from(values: &[u8]) -> Result<Sudoku, SudokuError> {
+        if values.len() != NUM_SQUARES {
+            return Err(SudokuError::InvalidLength(values.len()));
+        }
+
+        let mut grid = [0; NUM_SQUARES];
+        for (square, &value) in grid.iter_mut().zip(values) {
+            validate_value(value as u32)?;
+            *square = value as u32;
+        }
+
+        Ok(Sudoku {
+            grid,
+        })
+    }
+}
+
+/// The error type for the fallible operations on a [Sudoku].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SudokuError {
+    /// At least one of the coordinates is bigger than 8.
+    InvalidCoordinates {
+        /// The x coordinate that was supplied.
+        x: usize,
+        /// The y coordinate that was supplied.
+        y: usize,
+    },
+    /// A value bigger than 9 was supplied.
+    InvalidValue(u32),
+    /// A grid was supplied that does not consist of exactly 81 squares. Holds
+    /// the number of squares that was supplied instead.
+    InvalidLength(usize),
+    /// The same value appears twice in the same row, column or 3x3 cell.
+    ConflictingGivens {
+        /// The duplicated value.
+        value: u32,
+        /// The coordinates (x, y) of the first square containing `value`.
+        first: (usize, usize),
+        /// The coordinates (x, y) of the second square containing `value`.
+        second: (usize, usize),
+    },
+    /// A [Sudoku] could not be parsed from a string.
+    Parse(ParseSudokuError),
+}
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SudokuError::InvalidCoordinates { x, y } => {
+                write!(f, "x and y must both be <= 8 (x = {}, y = {})", x, y)
+            },
+            SudokuError::InvalidValue(value) => {
+                write!(f, "Value must be <= 9 (was {})", value)
+            },
+            SudokuError::InvalidLength(length) => {
+                write!(f, "a Sudoku grid must have {} squares (got {})", NUM_SQUARES, length)
+            },
+            SudokuError::ConflictingGivens { value, first, second } => {
+                write!(f, "value {} appears both at (x = {}, y = {}) and at (x = {}, y = {})",
+                       value, first.0, first.1, second.0, second.1)
+            },
+            SudokuError::Parse(error) => write!(f, "failed to parse Sudoku: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SudokuError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseSudokuError> for SudokuError {
+    fn from(error: ParseSudokuError) -> SudokuError {
+        SudokuError::Parse(error)
+    }
+}
+
+/// A group of 9 squares that must not contain any value twice: a row, a
+/// column or one of the 9 3x3 cells (called boxes here).
+///
+/// Rows are indexed by their y coordinate and columns by their x coordinate.
+/// Boxes are indexed from 0 to 8 in reading order, starting with the top left
+/// box, so the box containing the square (x / y) has the index
+/// `x / 3 + (y / 3) * 3`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum House {
+    /// The row with the given y coordinate.
+    Row(usize),
+    /// The column with the given x coordinate.
+    Column(usize),
+    /// The 3x3 box with the given index.
+    Box(usize),
+}
+
+impl House {
+
+    /// Get an [Iterator] over all 27 houses.
+    ///
+    /// The 9 rows come first, then the 9 columns and then the 9 boxes, each
+    /// in ascending order of their index.
+    pub fn all() -> impl Iterator<Item = House> {
+        (0..9).map(House::Row)
+            .chain((0..9).map(House::Column))
+            .chain((0..9).map(House::Box))
+    }
+
+    /// Get the box containing the square at the given coordinates.
+    ///
+    /// ```
+    /// use sudoku::House;
+    ///
+    /// assert_eq!(House::box_containing(4, 7), House::Box(7));
+    /// ```
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn box_containing(x: usize, y: usize) -> House {
+        validate_coordinates(x, y).unwrap_or_else(|error| panic!("{}", error));
+
+        House::Box(x / 3 + (y / 3) * 3)
+    }
+
+    /// Get the coordinates (x, y) of the 9 squares in this [House].
+    ///
+    /// Squares are returned in reading order (left to right, top to bottom).
+    ///
     /// ```
+    /// use sudoku::House;
     ///
-    /// This is not used for implementing the [Debug] or [std::fmt::Display]
-    /// traits, because, spanning 9 lines, the output is quite bulky, which is
-    /// not practical in every case.
-    // I think this function could be optimized some more (a lot of `String`s
-    // are created), but I don't feel like figuring out how to optimize it
-    // further.
-    #[allow(unstable_name_collisions)]
-    pub fn string_repr(&self) -> String {
-        let mut string_repr = self.grid
-            .iter()
-            .chunks(9)
-            .into_iter()
-            .map(|row| row
-                 .map(|value| value.to_string())
-                 .intersperse(" ".to_owned())
-                 .collect::<String>())
-            .intersperse("\n".to_owned())
-            .collect::<String>();
+    /// assert_eq!(House::Box(5).squares(), [(6, 3), (7, 3), (8, 3),
+    ///                                      (6, 4), (7, 4), (8, 4),
+    ///                                      (6, 5), (7, 5), (8, 5)]);
+    /// ```
+    ///
+    /// Panics if the index of the house is bigger than 8.
+    pub fn squares(&self) -> [(usize, usize); 9] {
+        if self.index() > 8 {
+            panic!("House index must be <= 8 (was {})", self.index());
+        }
+
+        match *self {
+            House::Row(y) => std::array::from_fn(|x| (x, y)),
+            House::Column(x) => std::array::from_fn(|y| (x, y)),
+            House::Box(index) => std::array::from_fn(|i| ((index % 3) * 3 + i % 3, (index / 3) * 3 + i / 3)),
+        }
+    }
 
-        string_repr.push_str("\n");
+    /// Get the index of this [House], no matter whether it is a row, a column
+    /// or a box.
+    pub fn index(&self) -> usize {
+        match *self {
+            House::Row(index) | House::Column(index) | House::Box(index) => index,
+        }
+    }
+}
 
-        string_repr
+/// Write the kind and the number of the [House], e.g. `box 6`.
+///
+/// Unlike the index, the number starts at 1, as it is common for puzzles
+/// shown to humans.
+impl fmt::Display for House {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            House::Row(index) => write!(f, "row {}", index + 1),
+            House::Column(index) => write!(f, "column {}", index + 1),
+            House::Box(index) => write!(f, "box {}", index + 1),
+        }
     }
 }
 
+/// A value that appears more than once within the same [House].
+///
+/// See [Sudoku::conflicts()].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Conflict {
+    /// The house containing the duplicated value.
+    pub house: House,
+    /// The duplicated value.
+    pub value: u32,
+    /// The coordinates (x, y) of all squares in `house` containing `value`.
+    pub squares: Vec<(usize, usize)>,
+}
+
+/// The number of solutions of a [Sudoku], see [Sudoku::count_solutions()].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SolutionCount {
+    /// There is no solution.
+    None,
+    /// There is exactly one solution.
+    Unique,
+    /// There are at least this many solutions (at least 2).
+    Multiple(usize),
+}
+
 /// Remember all values that may still be possible for a specific square.
 ///
 /// See also [NotesGrid].
@@ -699,8 +1444,8 @@ impl SudokuNote {
     /// in the square corresponding to this [SudokuNote].
     ///
     /// The iterator returns the values in ascending order.
-    fn possible_values(&self) -> SudokuNoteIter {
-        SudokuNoteIter::new(&self)
+    fn possible_values(&self) -> SudokuNoteIter<'_> {
+        SudokuNoteIter::new(self)
     }
 
     /// Reset this note to a state where every value could possibly be placed
@@ -718,10 +1463,10 @@ struct SudokuNoteIter<'a> {
 }
 
 impl SudokuNoteIter<'_> {
-    fn new(note: &SudokuNote) -> SudokuNoteIter {
+    fn new(note: &SudokuNote) -> SudokuNoteIter<'_> {
         SudokuNoteIter {
             position: 0,
-            note: note,
+            note,
         }
     }
 }
@@ -809,9 +1554,9 @@ impl NotesGrid {
 /// What happens with the notes for squares that already contain a value is not
 /// defined and may change in future versions.
 fn make_all_notes(notes: &mut NotesGrid, sudoku: &Sudoku) {
-    make_vertical_notes(notes, &sudoku);
-    make_horizontal_notes(notes, &sudoku);
-    make_in_cell_notes(notes, &sudoku);
+    make_vertical_notes(notes, sudoku);
+    make_horizontal_notes(notes, sudoku);
+    make_in_cell_notes(notes, sudoku);
 
     for note in &mut notes.grid {
         note.num_values_possible = 0;
@@ -821,20 +1566,24 @@ fn make_all_notes(notes: &mut NotesGrid, sudoku: &Sudoku) {
     }
 }
 
+/// Get the values that do not appear in `house` of `sudoku` as bit flags (bit
+/// `value - 1` is set if `value` is missing).
+fn missing_values(sudoku: &Sudoku, house: House) -> u32 {
+    house
+        .squares()
+        .into_iter()
+        .map(|(x, y)| sudoku.grid[x + y * 9])
+        .filter(|&value| value != 0)
+        .fold(SudokuNote::ALL_VALUES_POSSIBLE, |flags, value| flags & !(1 << (value - 1)))
+}
+
 /// Make vertical notes for every square in a [Sudoku].
 ///
 /// This functions leaves all [SudokuNote]s in the [NotesGrid] in an invalid
 /// state because the field `num_values_possible` is not updated.
 fn make_vertical_notes(notes: &mut NotesGrid, sudoku: &Sudoku) {
     for x in 0..9 {
-        let mut notes_mask = 0b111_111_111;
-        for y in 0..9 {
-            let value = sudoku.get_value(x, y);
-            if value == 0 {
-                continue;
-            }
-            notes_mask ^= 1 << (value - 1);
-        }
+        let notes_mask = missing_values(sudoku, House::Column(x));
         for y in 0..9 {
             notes.get_note_mut(x, y).notes_flags &= notes_mask;
         }
@@ -847,14 +1596,7 @@ fn make_vertical_notes(notes: &mut NotesGrid, sudoku: &Sudoku) {
 /// state because the field `num_values_possible` is not updated.
 fn make_horizontal_notes(notes: &mut NotesGrid, sudoku: &Sudoku) {
     for y in 0..9 {
-        let mut notes_mask = 0b111_111_111;
-        for x in 0..9 {
-            let value = sudoku.get_value(x, y);
-            if value == 0 {
-                continue;
-            }
-            notes_mask ^= 1 << (value - 1);
-        }
+        let notes_mask = missing_values(sudoku, House::Row(y));
         for x in 0..9 {
             notes.get_note_mut(x, y).notes_flags &= notes_mask;
         }
@@ -866,27 +1608,10 @@ fn make_horizontal_notes(notes: &mut NotesGrid, sudoku: &Sudoku) {
 /// This functions leaves all [SudokuNote]s in the [NotesGrid] in an invalid
 /// state because the field `num_values_possible` is not updated.
 fn make_in_cell_notes(notes: &mut NotesGrid, sudoku: &Sudoku) {
-    for cell_y in 0..3 {
-        for cell_x in 0..3 {
-            let mut notes_mask = 0b111_111_111;
-            for square_y in 0..3 {
-                for square_x in 0..3 {
-                    let x = cell_x * 3 + square_x;
-                    let y = cell_y * 3 + square_y;
-                    let value = sudoku.get_value(x, y);
-                    if value == 0 {
-                        continue;
-                    }
-                    notes_mask ^= 1 << (value - 1);
-                }
-            }
-            for square_y in 0..3 {
-                for square_x in 0..3 {
-                    let x = cell_x * 3 + square_x;
-                    let y = cell_y * 3 + square_y;
-                    notes.get_note_mut(x, y).notes_flags &= notes_mask;
-                }
-            }
+    for cell in 0..9 {
+        let notes_mask = missing_values(sudoku, House::Box(cell));
+        for (x, y) in House::Box(cell).squares() {
+            notes.get_note_mut(x, y).notes_flags &= notes_mask;
         }
     }
 }
@@ -928,8 +1653,8 @@ fn advance_with_notes(sudoku_grid: &mut Sudoku, notes: &mut NotesGrid) {
     let mut num_changes = u32::MAX;
         
     while num_changes != 0 {
-        make_all_notes(notes, &sudoku_grid);
-        num_changes = replace_notes_with_values(sudoku_grid, &notes);
+        make_all_notes(notes, sudoku_grid);
+        num_changes = replace_notes_with_values(sudoku_grid, notes);
     }
 }
 
@@ -950,30 +1675,79 @@ fn is_dead_end(sudoku_grid: &Sudoku, notes: &NotesGrid) -> bool {
     false
 }
 
-/// The [Iterator] returned by [Sudoku::find_all_solutions()] and the type that
-/// does the actual solving of [Sudoku]s.
+/// The [Iterator] behind [solver::Solutions] for
+/// [solver::Engine::Backtracking].
 ///
-/// `sudoku_grid` is a reference to the [Sudoku] puzzle to be solved by the solver.
+/// `sudoku_grid` is a copy of the [Sudoku] puzzle to be solved by the solver.
 ///
 /// `changes_stack` is a record of what changes needed to be made to the
 /// [Sudoku] to find the previous solution. This is required for the solver to
 /// know where to continue the search.
 ///
+/// `finished` is set as soon as it is known that there are no more solutions.
+/// An empty `changes_stack` cannot tell this apart from a search that has not
+/// started yet.
+///
 /// // TODO
 /// The solver would probably be faster if not changes, but the states of the
 /// grid was stored in the stack. The way it is now, the solver requires very
 /// little memory, but does quite some extra calculations because of that.
-struct AllSolutionsIterator<'a> {
-    sudoku_grid: &'a Sudoku,
+#[derive(Clone)]
+struct AllSolutionsIterator {
+    sudoku_grid: Sudoku,
+    branching: solver::Branching,
     changes_stack: Vec<ValueChange>,
+    finished: bool,
 }
 
-impl AllSolutionsIterator<'_> {
+impl AllSolutionsIterator {
+
+    /// Get the state of the search, see [solver::Solutions::state()].
+    fn state(&self) -> BacktrackingState {
+        BacktrackingState {
+            branching: self.branching,
+            changes_stack: self.changes_stack.clone(),
+            finished: self.finished,
+        }
+    }
+
+    /// Continue a search for the solutions of `sudoku_grid` from a saved
+    /// `state`.
+    ///
+    /// Return an error if the changes in `state` are out of bounds or not
+    /// made to distinct empty squares of `sudoku_grid`.
+    fn resume(sudoku_grid: &Sudoku, state: BacktrackingState) -> Result<AllSolutionsIterator, &'static str> {
+        let in_bounds = state.changes_stack.iter().all(|change| {
+            change.x <= 8 &&
+            change.y <= 8 &&
+            (1..=9).contains(&change.value) &&
+            change.house.is_none_or(|house| house.index() <= 8)
+        });
+
+        if !in_bounds {
+            return Err("value change out of bounds");
+        }
+
+        let mut changed = *sudoku_grid;
+        for change in &state.changes_stack {
+            if changed.get_value(change.x, change.y) != 0 {
+                return Err("value change on a square that is not empty");
+            }
+            changed.set_value(change.x, change.y, change.value);
+        }
+
+        Ok(AllSolutionsIterator {
+            sudoku_grid: *sudoku_grid,
+            branching: state.branching,
+            changes_stack: state.changes_stack,
+            finished: state.finished,
+        })
+    }
 
     /// Initialize a new [AllSolutionsIterator].
     ///
     /// Takes care of initializing `changes_stack`.
-    fn new(sudoku_grid: &Sudoku) -> AllSolutionsIterator {
+    fn new(sudoku_grid: &Sudoku, branching: solver::Branching) -> AllSolutionsIterator {
 
         // The maximum capacity needed for `changes_stack`.
         //
@@ -986,60 +1760,102 @@ impl AllSolutionsIterator<'_> {
         // it wouldn't significantly improve the performance of the solver
         // anyways.
         let num_empty_squares = sudoku_grid.num_empty_squares();
-        let stack_capacity = if num_empty_squares > 3 {
-            num_empty_squares - 3
-        } else {
-            0
-        };
+        let stack_capacity = num_empty_squares.saturating_sub(3);
 
         AllSolutionsIterator {
-            sudoku_grid,
+            sudoku_grid: *sudoku_grid,
+            branching,
             changes_stack: Vec::with_capacity(stack_capacity),
+            finished: false,
         }
     }
 
     /// Revert the last change made by the solver.
     ///
     /// Pop the last change off `changes_stack`, revert `sudoku_grid` and
-    /// `notes` to the state before the last change and set `last_value` to the
-    /// value of the last change.
-    ///
-    /// Return an Error if `changes_stack` is empty.
-    fn revert_last_change(&mut self, sudoku_grid: &mut Sudoku, notes: &mut NotesGrid, last_value: &mut u32) -> Result<(), &'static str> {
-        let last_value_change = match self.changes_stack.pop() {
-            Some(value_change) => value_change,
-            None => return Err("stack empty"),
-        };
-        *last_value = last_value_change.value;
-        *sudoku_grid = *self.sudoku_grid;
+    /// `notes` to the state before the last change and return the last change.
+    ///
+    /// Return `None` if `changes_stack` is empty.
+    fn revert_last_change(&mut self, sudoku_grid: &mut Sudoku, notes: &mut NotesGrid) -> Option<ValueChange> {
+        let last_value_change = self.changes_stack.pop()?;
+
+        *sudoku_grid = self.sudoku_grid;
         for value_change in &self.changes_stack {
             sudoku_grid.set_value(value_change.x, value_change.y, value_change.value);
         }
         notes.reset();
 
-        Ok(())
+        Some(last_value_change)
+    }
+
+    /// Find the first alternative of a new branch.
+    fn first_change(&self, sudoku_grid: &Sudoku, notes: &NotesGrid) -> Option<ValueChange> {
+        let candidates = notes.grid.map(|note| note.notes_flags as u16);
+
+        let branch = self
+            .branching
+            .choose(&candidates, |square| sudoku_grid.grid[square])
+            .expect("the grid has empty squares");
+
+        match branch {
+            solver::Branch::Square { square, values } => (values != 0).then(|| ValueChange {
+                x: square % 9,
+                y: square / 9,
+                value: values.trailing_zeros() + 1,
+                house: None,
+            }),
+            solver::Branch::House { house, value, squares } => (squares != 0).then(|| {
+                let (x, y) = house.squares()[squares.trailing_zeros() as usize];
+
+                ValueChange { x, y, value, house: Some(house) }
+            }),
+        }
+    }
+
+    /// Find the alternative of the same branch that comes after
+    /// `last_change`.
+    ///
+    /// If `last_change` has no house, the next alternative is the next
+    /// possible value for the same square. Otherwise it is the next square
+    /// of the house where the same value is possible.
+    fn next_change(sudoku_grid: &Sudoku, notes: &NotesGrid, last_change: ValueChange) -> Option<ValueChange> {
+        match last_change.house {
+            None => notes
+                .get_note(last_change.x, last_change.y)
+                .possible_values()
+                .find(|&possible_value| possible_value > last_change.value)
+                .map(|value| ValueChange { value, ..last_change }),
+            Some(house) => house
+                .squares()
+                .into_iter()
+                .skip_while(|&square| square != (last_change.x, last_change.y))
+                .skip(1)
+                .find(|&(x, y)| sudoku_grid.get_value(x, y) == 0 && notes.get_note(x, y).is_value_possible(last_change.value))
+                .map(|(x, y)| ValueChange { x, y, ..last_change }),
+        }
     }
 }
 
-impl Iterator for AllSolutionsIterator<'_> {
+impl Iterator for AllSolutionsIterator {
     type Item = Sudoku;
 
     fn next(&mut self) -> Option<Sudoku> {
 
-        let mut sudoku_grid = *self.sudoku_grid;
+        if self.finished {
+            return None;
+        }
+
+        let mut sudoku_grid = self.sudoku_grid;
         let mut notes = NotesGrid::new();
 
-        // `last_value` ensures that the solver will not just find the same
+        // `last_change` ensures that the solver will not just find the same
         // solution over and over again
         //
-        // if this is the search for the first solution, set `last_value` to 0,
-        // else remove the last value on the stack (otherwise the exact same
-        // solution that was already found will be returned) and set last_value
-        // to that value
-        let mut last_value = match self.changes_stack.pop() {
-            Some(value_change) => value_change.value,
-            None => 0,
-        };
+        // if this is the search for the first solution, there is no last
+        // change, else remove the last change on the stack (otherwise the
+        // exact same solution that was already found will be returned) and
+        // continue with the next alternative of the same branch
+        let mut last_change = self.changes_stack.pop();
 
         for value_change in &self.changes_stack {
             sudoku_grid.set_value(value_change.x, value_change.y, value_change.value);
@@ -1052,63 +1868,105 @@ impl Iterator for AllSolutionsIterator<'_> {
             // advance_with_notes() does not guarantee that the grid it
             // produces is valid, so it has to be checked here
             if (!sudoku_grid.is_valid()) || is_dead_end(&sudoku_grid, &notes) {
-                match self.revert_last_change(&mut sudoku_grid, &mut notes, &mut last_value) {
-                    Ok(_) => continue 'outer,
+                match self.revert_last_change(&mut sudoku_grid, &mut notes) {
+                    Some(change) => {
+                        last_change = Some(change);
+                        continue 'outer;
+                    },
                     // if the stack is empty
-                    Err(_) => return None,
+                    None => {
+                        self.finished = true;
+                        return None;
+                    },
                 };
             }
 
             // if a Sudoku grid is valid and has no empty squares, that means
             // it is solved
             if sudoku_grid.num_empty_squares() == 0 {
+                // If no guesses were needed to get here, this is the only
+                // solution. Searching again would just find it once more.
+                if self.changes_stack.is_empty() {
+                    self.finished = true;
+                }
                 return Some(sudoku_grid);
             }
 
-            for y in 0..9 {
-                for x in 0..9 {
-                    for possible_value in notes.get_note(x, y).possible_values() {
-                        // The second part of this expression is required
-                        // because the notes of squares that already contain a
-                        // value may still allow some possible values. See the
-                        // documentation for sudoku::make_all_notes() for more
-                        // information.
-                        if possible_value > last_value && sudoku_grid.get_value(x, y) == 0 {
-                            last_value = 0;
-                            sudoku_grid.set_value(x, y, possible_value);
-                            self.changes_stack.push(ValueChange { x, y, value: possible_value });
-                            continue 'outer;
-                        }
-                    }
-                }
+            // After a revert, the grid is in the same state as when the
+            // reverted change was made, so the search continues with the next
+            // alternative of the same branch. Branching somewhere else instead
+            // would visit the same solutions again.
+            let change = match last_change.take() {
+                Some(last_change) => AllSolutionsIterator::next_change(&sudoku_grid, &notes, last_change),
+                None => self.first_change(&sudoku_grid, &notes),
+            };
+
+            if let Some(change) = change {
+                sudoku_grid.set_value(change.x, change.y, change.value);
+                self.changes_stack.push(change);
+                continue 'outer;
             }
 
-            match self.revert_last_change(&mut sudoku_grid, &mut notes, &mut last_value) {
-                Ok(_) => continue 'outer,
+            match self.revert_last_change(&mut sudoku_grid, &mut notes) {
+                Some(change) => {
+                    last_change = Some(change);
+                    continue 'outer;
+                },
                 // if the stack is empty
-                Err(_) => return None,
+                None => {
+                    self.finished = true;
+                    return None;
+                },
             };
         }
     }
 }
 
+/// The saved state of an [AllSolutionsIterator].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct BacktrackingState {
+    branching: solver::Branching,
+    changes_stack: Vec<ValueChange>,
+    finished: bool,
+}
+
 /// Stores one change of the solver.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+///
+/// `house` is set if the solver branched on the squares of a house where
+/// `value` is possible instead of on the values possible for a square.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 struct ValueChange {
     x: usize,
     y: usize,
     value: u32,
+    house: Option<House>,
 }
 
 #[cfg(test)]
 mod tests {
 
+    use itertools::Itertools;
+
+    use crate::Conflict;
+    use crate::candidates::Candidates;
+    use crate::House;
+    use crate::ParseSudokuError;
+    use crate::SolutionCount;
     use crate::Sudoku;
+    use crate::SudokuError;
     use crate::SudokuNote;
     use crate::NotesGrid;
+    use crate::solver::{Branching, Engine, Solver};
 
     use crate::NUM_SQUARES;
 
+    /// A puzzle with a unique solution that the
+    /// [LogicalSolver](crate::logic::LogicalSolver) solves without guessing,
+    /// shared by the tests of all modules.
+    ///
+    /// Values generated with http://www.opensky.ca/sudoku
+    pub(crate) const PUZZLE: &str = "..1.2.9..9...4..2..2..98.51.17......4..7.6..9......61.13.87..6..7..5...4..5.6.3..";
+
     /// A very simple Sudoku puzzle.
     ///
     /// Generated with https://sudokukingdom.com/very-easy-sudoku.php (accessed 15.08.2022)
@@ -1220,6 +2078,55 @@ mod tests {
                                 0, 0, 0, 0, 0, 0, 0, 0, 0]);
     }
 
+    #[test]
+    fn try_get_value_invalid_coordinates() {
+        let grid = Sudoku::new_empty();
+
+        assert_eq!(grid.try_get_value(9, 0), Err(SudokuError::InvalidCoordinates { x: 9, y: 0 }));
+        assert_eq!(grid.try_get_value(0, 12), Err(SudokuError::InvalidCoordinates { x: 0, y: 12 }));
+    }
+
+    #[test]
+    fn try_set_value_invalid_input_leaves_grid_untouched() {
+        let mut grid = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
+
+        assert_eq!(grid.try_set_value(0, 0, 10), Err(SudokuError::InvalidValue(10)));
+        assert_eq!(grid.try_set_value(9, 9, 1), Err(SudokuError::InvalidCoordinates { x: 9, y: 9 }));
+
+        assert_eq!(grid, Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU));
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let values: Vec<u8> = EXTREMELY_SIMPLE_SUDOKU.iter().map(|&value| value as u8).collect();
+
+        assert_eq!(Sudoku::try_from(values.as_slice()), Ok(Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU)));
+    }
+
+    #[test]
+    fn try_from_slice_invalid_value() {
+        let mut values = [0u8; NUM_SQUARES];
+        values[40] = 200;
+
+        assert_eq!(Sudoku::try_from(&values[..]), Err(SudokuError::InvalidValue(200)));
+    }
+
+    #[test]
+    fn validate_conflict_in_3x3_cell() {
+        let mut grid = Sudoku::new_empty();
+        grid.set_value(3, 3, 8);
+        grid.set_value(5, 4, 8);
+
+        assert_eq!(grid.validate(), Err(SudokuError::ConflictingGivens { value: 8, first: (3, 3), second: (5, 4) }));
+    }
+
+    #[test]
+    fn parse_error_converts_to_sudoku_error() {
+        let error: SudokuError = Sudoku::parse_line("").unwrap_err().into();
+
+        assert_eq!(error, SudokuError::Parse(ParseSudokuError::TooFewSquares(0)));
+    }
+
     #[test]
     fn is_solved_yes() {
         // Values generated with http://www.opensky.ca/sudoku
@@ -1347,6 +2254,121 @@ mod tests {
         assert!(!unsolved_sudoku.is_solved());
     }
     
+    #[test]
+    fn conflicts_none_for_valid_sudoku() {
+        let sudoku = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU_SOLUTION);
+
+        assert_eq!(sudoku.conflicts(), vec![]);
+    }
+
+    #[test]
+    fn conflicts_square_in_row_column_and_box() {
+        // Original values taken from EXTREMELY_SIMPLE_SUDOKU
+        //
+        // Note the `9` inserted at position (x = 7 / y = 1)
+        let mut sudoku = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
+        sudoku.set_value(7, 1, 9);
+
+        let expected = vec![
+            Conflict { house: House::Row(1), value: 9, squares: vec![(2, 1), (7, 1)] },
+            Conflict { house: House::Column(7), value: 9, squares: vec![(7, 1), (7, 5)] },
+            Conflict { house: House::Box(2), value: 9, squares: vec![(8, 0), (7, 1)] },
+        ];
+
+        assert_eq!(sudoku.conflicts(), expected);
+    }
+
+    #[test]
+    fn conflicts_more_than_two_squares() {
+        let sudoku = Sudoku::new_from_array([1, 2, 3, 1, 2, 3, 1, 2, 3,
+                                             4, 5, 6, 4, 5, 6, 4, 5, 6,
+                                             7, 8, 9, 7, 8, 9, 7, 8, 9,
+                                             2, 3, 4, 2, 3, 4, 2, 3, 4,
+                                             5, 6, 7, 5, 6, 7, 5, 6, 7,
+                                             8, 9, 1, 8, 9, 1, 8, 9, 1,
+                                             3, 4, 5, 3, 4, 5, 3, 4, 5,
+                                             6, 7, 8, 6, 7, 8, 6, 7, 8,
+                                             9, 1, 2, 9, 1, 2, 9, 1, 2]);
+
+        let conflicts = sudoku.conflicts();
+
+        // 3 duplicated values in each of the 9 rows
+        assert_eq!(conflicts.len(), 27);
+        assert_eq!(conflicts[0], Conflict { house: House::Row(0), value: 1, squares: vec![(0, 0), (3, 0), (6, 0)] });
+    }
+
+    #[test]
+    fn candidates_contain_solution() {
+        let sudoku = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
+        let solution = sudoku.find_solution().unwrap();
+        let candidates = sudoku.candidates();
+
+        for y in 0..9 {
+            for x in 0..9 {
+                if sudoku.get_value(x, y) == 0 {
+                    assert!(candidates[x + y * 9].contains(solution.get_value(x, y)));
+                } else {
+                    assert!(candidates[x + y * 9].is_empty());
+                }
+                assert_eq!(sudoku.candidates_at(x, y), candidates[x + y * 9]);
+            }
+        }
+
+        assert_eq!(sudoku.candidates_at(2, 2), Candidates::from_iter([3]));
+    }
+
+    #[test]
+    fn candidates_of_invalid_sudoku() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set_value(0, 0, 5);
+        sudoku.set_value(8, 0, 5);
+
+        // A duplicated value must not count as missing from the row
+        assert!(!sudoku.candidates_at(4, 0).contains(5));
+
+        let candidates = sudoku.candidates();
+        for y in 0..9 {
+            for x in 0..9 {
+                assert_eq!(sudoku.candidates_at(x, y), candidates[x + y * 9]);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn candidates_at_panics_on_invalid_coordinates() {
+        Sudoku::new_empty().candidates_at(9, 0);
+    }
+
+    #[test]
+    fn house_display() {
+        assert_eq!(House::Row(0).to_string(), "row 1");
+        assert_eq!(House::Column(6).to_string(), "column 7");
+        assert_eq!(House::Box(5).to_string(), "box 6");
+    }
+
+    #[test]
+    fn house_squares_cover_grid() {
+        for house_kind in [House::Row, House::Column, House::Box] {
+            let mut covered = [false; NUM_SQUARES];
+
+            for index in 0..9 {
+                for (x, y) in house_kind(index).squares() {
+                    assert!(!covered[x + y * 9]);
+                    covered[x + y * 9] = true;
+                }
+            }
+
+            assert!(covered.iter().all(|&covered| covered));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn house_squares_panics_on_invalid_index() {
+        House::Box(9).squares();
+    }
+
     #[test]
     fn find_solution_extremely_simple_sudoku() {
         let expected_solution = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU_SOLUTION);
@@ -1463,6 +2485,88 @@ mod tests {
         }
     }
 
+    /// This used to be a bug. If a solution was found without guessing, the
+    /// iterator kept returning that same solution forever.
+    #[test]
+    fn find_all_solutions_no_guessing_needed() {
+        let puzzle = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
+        let solved = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU_SOLUTION);
+
+        for branching in Branching::ALL {
+            let solver = Solver::new().engine(Engine::Backtracking).branching(branching);
+
+            assert_eq!(solver.find_all_solutions(&puzzle).take(2).count(), 1);
+            assert_eq!(solver.find_all_solutions(&solved).take(2).count(), 1);
+        }
+    }
+
+    /// This used to be a bug. After backtracking, the solver could move on to
+    /// another square instead of trying the next value of the square it had
+    /// reverted, which produced the same solutions over and over again (161
+    /// solutions were found for this puzzle).
+    #[test]
+    fn find_all_solutions_no_duplicates() {
+        // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+        let puzzle = Sudoku::new_from_array([3, 0, 9, 6, 0, 0, 4, 0, 0,
+                                             0, 0, 0, 7, 0, 9, 0, 0, 0,
+                                             0, 8, 7, 0, 0, 0, 0, 0, 0,
+                                             7, 5, 0, 0, 6, 0, 2, 3, 0,
+                                             6, 0, 0, 9, 0, 4, 0, 0, 8,
+                                             0, 2, 8, 0, 5, 0, 0, 4, 1,
+                                             0, 0, 0, 0, 0, 0, 5, 9, 0,
+                                             0, 0, 0, 1, 9, 6, 0, 0, 7,
+                                             0, 0, 6, 0, 0, 0, 1, 0, 4]);
+
+        for branching in Branching::ALL {
+            let solver = Solver::new().engine(Engine::Backtracking).branching(branching);
+            let solutions = solver.find_all_solutions(&puzzle).take(4).collect::<Vec<_>>();
+
+            assert_eq!(solutions.len(), 3);
+            assert!(solutions.iter().all_unique());
+        }
+    }
+
+    #[test]
+    fn find_all_solutions_stays_exhausted() {
+        let dead_end = Sudoku::new_from_array([1, 2, 0, 4, 5, 6, 7, 8, 9,
+                                               0, 0, 3, 0, 0, 0, 0, 0, 0,
+                                               0, 0, 0, 0, 0, 0, 0, 0, 0,
+                                               0, 0, 0, 0, 0, 0, 0, 0, 0,
+                                               0, 0, 0, 0, 0, 0, 0, 0, 0,
+                                               0, 0, 0, 0, 0, 0, 0, 0, 0,
+                                               0, 0, 0, 0, 0, 0, 0, 0, 0,
+                                               0, 0, 0, 0, 0, 0, 0, 0, 0,
+                                               0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut solutions = dead_end.find_all_solutions();
+
+        assert_eq!(solutions.next(), None);
+        assert_eq!(solutions.next(), None);
+    }
+
+    #[test]
+    fn count_solutions() {
+        let unique = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
+        assert_eq!(unique.count_solutions(10), SolutionCount::Unique);
+        assert!(unique.has_unique_solution());
+
+        let unsolvable: Sudoku = ("12.456789..3".to_owned() + &".".repeat(69)).parse().unwrap();
+        assert_eq!(unsolvable.count_solutions(10), SolutionCount::None);
+        assert!(!unsolvable.has_unique_solution());
+
+        let empty = Sudoku::new_empty();
+        assert_eq!(empty.count_solutions(5), SolutionCount::Multiple(5));
+        assert!(!empty.has_unique_solution());
+    }
+
+    #[test]
+    fn count_solutions_small_limit() {
+        let empty = Sudoku::new_empty();
+
+        assert_eq!(empty.count_solutions(0), SolutionCount::Multiple(2));
+        assert_eq!(empty.count_solutions(1), SolutionCount::Multiple(2));
+    }
+
     #[test]
     fn num_occurrences_of_and_num_empty_squares() {
         let sudoku = Sudoku::new_from_array([2, 0, 0, 8, 7, 0, 0, 0, 0,
@@ -1527,6 +2631,54 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn parse_line_accepts_all_blank_markers() {
+        let line = "7.6_5*419 0_9.748*. 48.6.9..5 ..89.5.34 942.6.1.. 3..4.1.92 .9...6581 53.14..2. .6159.3..";
+
+        let sudoku: Sudoku = line.parse().expect("line is valid");
+
+        assert_eq!(sudoku, Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU));
+    }
+
+    #[test]
+    fn parse_line_round_trips_string_repr() {
+        let sudoku = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
+
+        assert_eq!(Sudoku::parse_line(&sudoku.string_repr()), Ok(sudoku));
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let sudoku = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
+
+        let json = serde_json::to_string(&sudoku).unwrap();
+
+        assert_eq!(json, "\"706050419009074800480609005008905034942060100300401092090006581530140020061590300\"");
+        assert_eq!(serde_json::from_str::<Sudoku>(&json).unwrap(), sudoku);
+        assert!(serde_json::from_str::<Sudoku>("\"123\"").is_err());
+    }
+
+    #[test]
+    fn parse_line_invalid_character() {
+        let line = "7.6.5.419..9.748..48.6.9..5..89.5.34942.6.1..3..4.1.92.9...658153.a4..2..6159.3..";
+
+        assert_eq!(Sudoku::parse_line(line),
+                   Err(ParseSudokuError::InvalidCharacter { position: 66, character: 'a' }));
+    }
+
+    #[test]
+    fn parse_line_too_few_squares() {
+        assert_eq!(Sudoku::parse_line("123 456\n789"), Err(ParseSudokuError::TooFewSquares(9)));
+    }
+
+    #[test]
+    fn parse_line_too_many_squares() {
+        let line = "0".repeat(NUM_SQUARES) + " 1";
+
+        assert_eq!(Sudoku::parse_line(&line),
+                   Err(ParseSudokuError::TooManySquares { position: NUM_SQUARES + 1 }));
+    }
+
     // SudokuNote methods
 
     #[test]
@@ -1621,6 +2773,25 @@ mod tests {
         assert_eq!(notes.get_note(7, 8).possible_values().collect::<Vec<u32>>(), vec![4, 7]);
     }
 
+    #[test]
+    fn make_all_notes_with_duplicates() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set_value(0, 0, 5);
+        sudoku.set_value(8, 0, 5);
+        sudoku.set_value(2, 1, 3);
+        sudoku.set_value(2, 7, 3);
+        sudoku.set_value(6, 6, 7);
+        sudoku.set_value(8, 8, 7);
+
+        let mut notes = NotesGrid::new();
+        crate::make_all_notes(&mut notes, &sudoku);
+
+        // a value that appears twice is still not possible in its houses
+        assert!(!notes.get_note(4, 0).is_value_possible(5));
+        assert!(!notes.get_note(2, 4).is_value_possible(3));
+        assert!(!notes.get_note(7, 7).is_value_possible(7));
+    }
+
     #[test]
     fn make_vertical_notes() {
         let sudoku = Sudoku::new_from_array(EXTREMELY_SIMPLE_SUDOKU);
@@ -0,0 +1,1032 @@
+//! A solver that solves [Sudoku]s the way a human would.
+//!
+//! Contrary to [Sudoku::find_solution()], which guesses whenever it gets
+//! stuck, the [LogicalSolver] only applies named solving techniques (see
+//! [Technique]) and solves a puzzle one [SolveStep] at a time. This makes it
+//! possible to show how a puzzle can be solved, but it also means that the
+//! [LogicalSolver] gets stuck on puzzles that require techniques it does not
+//! know about.
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::logic::{LogicalSolver, Technique};
+//!
+//! // Values generated with http://www.opensky.ca/sudoku
+//! let sudoku = Sudoku::new_from_array([4, 3, 0, 0, 0, 9, 8, 0, 0,
+//!                                      1, 9, 0, 8, 0, 0, 0, 0, 5,
+//!                                      0, 0, 0, 7, 2, 4, 0, 0, 0,
+//!                                      0, 6, 1, 0, 9, 0, 0, 2, 0,
+//!                                      0, 0, 0, 0, 0, 0, 0, 0, 0,
+//!                                      0, 2, 0, 0, 5, 0, 6, 9, 0,
+//!                                      0, 0, 0, 6, 3, 7, 0, 0, 0,
+//!                                      8, 0, 0, 0, 0, 5, 0, 3, 1,
+//!                                      0, 0, 5, 1, 0, 0, 0, 7, 6]);
+//!
+//! let mut solver = LogicalSolver::new(&sudoku);
+//!
+//! let first_step = solver.step().expect("the puzzle is not stuck yet");
+//! assert_eq!(first_step.technique, Technique::HiddenSingle);
+//!
+//! // Apply all remaining steps
+//! for _step in solver.by_ref() {}
+//!
+//! assert!(solver.sudoku().is_solved());
+//! ```
+
+use crate::{House, Sudoku, NUM_SQUARES};
+use crate::candidates::Candidates;
+use crate::explain::{English, StepFormatter};
+
+use itertools::Itertools;
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// Candidate flags of a square where every value is still possible (bit
+/// `value - 1` is set if `value` is possible).
+const ALL_CANDIDATES: u16 = 0b111_111_111;
+
+/// A named solving technique known by the [LogicalSolver].
+///
+/// The techniques are declared in ascending order of difficulty, which is
+/// also the order in which the [LogicalSolver] tries them. They are serialized
+/// in snake case, e.g. `"hidden_single"`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Technique {
+    /// A value can only go into one square of a house.
+    HiddenSingle,
+    /// Only one value can go into a square.
+    NakedSingle,
+    /// All candidates for a value within a box are in the same row or column,
+    /// so the value can be removed from the rest of that row or column.
+    Pointing,
+    /// All candidates for a value within a row or column are in the same box,
+    /// so the value can be removed from the rest of that box.
+    Claiming,
+    /// Two squares of a house can only contain the same two values.
+    NakedPair,
+    /// Two rows (or columns) whose candidates for a value lie in the same two
+    /// columns (or rows).
+    XWing,
+    /// Two values can only go into the same two squares of a house.
+    HiddenPair,
+    /// Three squares of a house can only contain the same three values.
+    NakedTriple,
+    /// The three line version of [Technique::XWing].
+    Swordfish,
+    /// Three values can only go into the same three squares of a house.
+    HiddenTriple,
+    /// A square with two candidates XY and two squares it sees with the
+    /// candidates XZ and YZ. Z can be removed from squares seeing both of
+    /// them.
+    XYWing,
+    /// Like [Technique::XYWing], but the pivot square also has Z as a
+    /// candidate.
+    XYZWing,
+    /// Four squares of a house can only contain the same four values.
+    NakedQuad,
+    /// The four line version of [Technique::XWing].
+    Jellyfish,
+    /// Four values can only go into the same four squares of a house.
+    HiddenQuad,
+}
+
+impl Technique {
+
+    /// All techniques, in the order in which the [LogicalSolver] tries them.
+    pub const ALL: [Technique; 15] = [
+        Technique::HiddenSingle,
+        Technique::NakedSingle,
+        Technique::Pointing,
+        Technique::Claiming,
+        Technique::NakedPair,
+        Technique::XWing,
+        Technique::HiddenPair,
+        Technique::NakedTriple,
+        Technique::Swordfish,
+        Technique::HiddenTriple,
+        Technique::XYWing,
+        Technique::XYZWing,
+        Technique::NakedQuad,
+        Technique::Jellyfish,
+        Technique::HiddenQuad,
+    ];
+
+    /// Get the common English name of this technique.
+    ///
+    /// ```
+    /// use sudoku::logic::Technique;
+    ///
+    /// assert_eq!(Technique::XYWing.name(), "XY-Wing");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::HiddenSingle => "Hidden Single",
+            Technique::NakedSingle => "Naked Single",
+            Technique::Pointing => "Pointing",
+            Technique::Claiming => "Claiming",
+            Technique::NakedPair => "Naked Pair",
+            Technique::XWing => "X-Wing",
+            Technique::HiddenPair => "Hidden Pair",
+            Technique::NakedTriple => "Naked Triple",
+            Technique::Swordfish => "Swordfish",
+            Technique::HiddenTriple => "Hidden Triple",
+            Technique::XYWing => "XY-Wing",
+            Technique::XYZWing => "XYZ-Wing",
+            Technique::NakedQuad => "Naked Quad",
+            Technique::Jellyfish => "Jellyfish",
+            Technique::HiddenQuad => "Hidden Quad",
+        }
+    }
+
+    /// Get the rating of this technique on a scale similar to the one of
+    /// Sudoku Explainer.
+    ///
+    /// Harder techniques have higher ratings. The values are taken from Sudoku
+    /// Explainer where it knows the technique. Note that Sudoku Explainer
+    /// rates hidden singles within a box a bit lower (1.2) than this function
+    /// does, see [crate::rating] for how that is taken into account.
+    ///
+    /// ```
+    /// use sudoku::logic::Technique;
+    ///
+    /// assert!(Technique::XWing.rating() > Technique::NakedPair.rating());
+    /// ```
+    pub fn rating(&self) -> f32 {
+        match self {
+            Technique::HiddenSingle => 1.5,
+            Technique::NakedSingle => 2.3,
+            Technique::Pointing => 2.6,
+            Technique::Claiming => 2.8,
+            Technique::NakedPair => 3.0,
+            Technique::XWing => 3.2,
+            Technique::HiddenPair => 3.4,
+            Technique::NakedTriple => 3.6,
+            Technique::Swordfish => 3.8,
+            Technique::HiddenTriple => 4.0,
+            Technique::XYWing => 4.2,
+            Technique::XYZWing => 4.4,
+            Technique::NakedQuad => 5.0,
+            Technique::Jellyfish => 5.2,
+            Technique::HiddenQuad => 5.4,
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A value that is placed into a square.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Placement {
+    /// The x coordinate of the square.
+    pub x: usize,
+    /// The y coordinate of the square.
+    pub y: usize,
+    /// The value placed into the square.
+    pub value: u32,
+}
+
+/// A candidate that is removed from a square.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Elimination {
+    /// The x coordinate of the square.
+    pub x: usize,
+    /// The y coordinate of the square.
+    pub y: usize,
+    /// The value that can no longer be placed into the square.
+    pub value: u32,
+}
+
+/// One step of the [LogicalSolver].
+///
+/// Describes which [Technique] was applied where and what was deduced from
+/// it. Every step has at least one placement or elimination.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SolveStep {
+    /// The technique that was applied.
+    pub technique: Technique,
+    /// The houses the technique was applied to, e.g. the box of a
+    /// [Technique::Pointing] followed by the row or column it points to.
+    pub houses: Vec<House>,
+    /// The coordinates (x, y) of the squares forming the pattern of the
+    /// technique.
+    pub squares: Vec<(usize, usize)>,
+    /// The values forming the pattern of the technique, in ascending order.
+    pub values: Vec<u32>,
+    /// The values placed by this step.
+    pub placements: Vec<Placement>,
+    /// The candidates removed by this step.
+    pub eliminations: Vec<Elimination>,
+}
+
+/// Explain the step in English, see [English].
+impl fmt::Display for SolveStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&English.format_step(self))
+    }
+}
+
+/// A solver that solves a [Sudoku] using human solving techniques only.
+///
+/// The solver keeps track of the candidates of every empty square. Initially,
+/// these are all the values not yet present in the row, column or box of the
+/// square. Each [SolveStep] either places values or removes candidates.
+///
+/// [LogicalSolver] is also an [Iterator] which applies and returns one step
+/// after the other until the [Sudoku] is solved or no technique applies
+/// anymore.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogicalSolver {
+    sudoku: Sudoku,
+    candidates: [u16; NUM_SQUARES],
+}
+
+impl LogicalSolver {
+
+    /// Initialize a new [LogicalSolver] for the given [Sudoku].
+    pub fn new(sudoku: &Sudoku) -> LogicalSolver {
+        let candidates = sudoku.candidates();
+
+        LogicalSolver {
+            sudoku: *sudoku,
+            candidates: candidates.map(|candidates| candidates.bits()),
+        }
+    }
+
+    /// Get the [Sudoku] in its current state, with all placements made so far.
+    pub fn sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+
+    /// Check if `value` is still a candidate for the square at (`x` / `y`).
+    ///
+    /// Squares that already contain a value have no candidates.
+    ///
+    /// Panics if the coordinates or the value are out of bounds.
+    pub fn is_candidate(&self, x: usize, y: usize, value: u32) -> bool {
+        if x > 8 || y > 8 || !(1..=9).contains(&value) {
+            panic!("invalid candidate (x = {}, y = {}, value = {})", x, y, value);
+        }
+
+        self.candidates[x + y * 9] & value_flag(value) != 0
+    }
+
+    /// Get the remaining [Candidates] of every square, in the order of
+    /// [Sudoku::candidates()].
+    pub fn candidates(&self) -> [Candidates; NUM_SQUARES] {
+        self.candidates.map(|flags| Candidates::from_bits(flags).expect("only the flags of the values 1 to 9 are ever set"))
+    }
+
+    /// Get the remaining [Candidates] of the square at (`x` / `y`).
+    ///
+    /// Initially, these are the [Sudoku::candidates()], every [SolveStep]
+    /// applied since may have removed some of them.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn candidates_at(&self, x: usize, y: usize) -> Candidates {
+        if x > 8 || y > 8 {
+            panic!("invalid coordinates (x = {}, y = {})", x, y);
+        }
+
+        self.candidates()[x + y * 9]
+    }
+
+    /// Check if the solver has run into a contradiction.
+    ///
+    /// This is the case if the [Sudoku] is invalid, if an empty square has no
+    /// candidates left or if a value can no longer be placed anywhere in a
+    /// house that does not contain it yet. It can only happen for [Sudoku]s
+    /// that have no solution.
+    pub fn has_contradiction(&self) -> bool {
+        if !self.sudoku.is_valid() {
+            return true;
+        }
+
+        for y in 0..9 {
+            for x in 0..9 {
+                if self.sudoku.get_value(x, y) == 0 && self.candidates[x + y * 9] == 0 {
+                    return true;
+                }
+            }
+        }
+
+        for house in House::all() {
+            let mut covered = 0;
+            for (x, y) in house.squares() {
+                covered |= self.candidates[x + y * 9];
+                let value = self.sudoku.get_value(x, y);
+                if value != 0 {
+                    covered |= value_flag(value);
+                }
+            }
+            if covered != ALL_CANDIDATES {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Find the next step without applying it.
+    ///
+    /// The techniques are tried in the order of [Technique::ALL], so the
+    /// simplest applicable step is returned. Return `None` if the [Sudoku] is
+    /// solved, if no technique applies or if the solver has run into a
+    /// contradiction (see [LogicalSolver::has_contradiction()]).
+    pub fn next_step(&self) -> Option<SolveStep> {
+        if !self.sudoku.has_empty_squares() || self.has_contradiction() {
+            return None;
+        }
+
+        Technique::ALL
+            .iter()
+            .find_map(|&technique| self.find(technique))
+    }
+
+    /// Find the next step using only the given technique, without applying it.
+    pub fn find(&self, technique: Technique) -> Option<SolveStep> {
+        match technique {
+            Technique::HiddenSingle => self.find_hidden_single(),
+            Technique::NakedSingle => self.find_naked_single(),
+            Technique::Pointing => self.find_pointing(),
+            Technique::Claiming => self.find_claiming(),
+            Technique::NakedPair => self.find_naked_subset(technique, 2),
+            Technique::NakedTriple => self.find_naked_subset(technique, 3),
+            Technique::NakedQuad => self.find_naked_subset(technique, 4),
+            Technique::HiddenPair => self.find_hidden_subset(technique, 2),
+            Technique::HiddenTriple => self.find_hidden_subset(technique, 3),
+            Technique::HiddenQuad => self.find_hidden_subset(technique, 4),
+            Technique::XWing => self.find_fish(technique, 2),
+            Technique::Swordfish => self.find_fish(technique, 3),
+            Technique::Jellyfish => self.find_fish(technique, 4),
+            Technique::XYWing => self.find_xy_wing(),
+            Technique::XYZWing => self.find_xyz_wing(),
+        }
+    }
+
+    /// Apply a step to the [Sudoku] and the candidates.
+    ///
+    /// Placing a value removes it from the candidates of every square in the
+    /// same row, column and box.
+    pub fn apply(&mut self, step: &SolveStep) {
+        for placement in &step.placements {
+            self.sudoku.set_value(placement.x, placement.y, placement.value);
+            self.candidates[placement.x + placement.y * 9] = 0;
+
+            for (x, y) in peers(placement.x, placement.y) {
+                self.candidates[x + y * 9] &= !value_flag(placement.value);
+            }
+        }
+
+        for elimination in &step.eliminations {
+            self.candidates[elimination.x + elimination.y * 9] &= !value_flag(elimination.value);
+        }
+    }
+
+    /// Find the next step and apply it.
+    ///
+    /// Return the applied step or `None` if no step was found (see
+    /// [LogicalSolver::next_step()]).
+    pub fn step(&mut self) -> Option<SolveStep> {
+        let step = self.next_step()?;
+        self.apply(&step);
+
+        Some(step)
+    }
+
+    /// Get the candidate flags of the square at (`x` / `y`).
+    fn candidate_flags(&self, x: usize, y: usize) -> u16 {
+        self.candidates[x + y * 9]
+    }
+
+    /// Get the squares in `house` where `value` is still a candidate.
+    fn positions(&self, house: House, value: u32) -> Vec<(usize, usize)> {
+        house
+            .squares()
+            .into_iter()
+            .filter(|&(x, y)| self.candidate_flags(x, y) & value_flag(value) != 0)
+            .collect()
+    }
+
+    /// Get the eliminations of `value` from all `squares` where it is still a
+    /// candidate.
+    fn eliminations_of(&self, value: u32, squares: impl IntoIterator<Item = (usize, usize)>) -> Vec<Elimination> {
+        squares
+            .into_iter()
+            .filter(|&(x, y)| self.candidate_flags(x, y) & value_flag(value) != 0)
+            .map(|(x, y)| Elimination { x, y, value })
+            .collect()
+    }
+
+    fn find_hidden_single(&self) -> Option<SolveStep> {
+        // Boxes are the easiest to scan for a human, so they are checked first
+        let houses = (0..9).map(House::Box)
+            .chain((0..9).map(House::Row))
+            .chain((0..9).map(House::Column));
+
+        for house in houses {
+            for value in 1..=9 {
+                let positions = self.positions(house, value);
+                if let [(x, y)] = positions[..] {
+                    return Some(SolveStep {
+                        technique: Technique::HiddenSingle,
+                        houses: vec![house],
+                        squares: vec![(x, y)],
+                        values: vec![value],
+                        placements: vec![Placement { x, y, value }],
+                        eliminations: vec![],
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn find_naked_single(&self) -> Option<SolveStep> {
+        for y in 0..9 {
+            for x in 0..9 {
+                let flags = self.candidate_flags(x, y);
+                if flags.count_ones() == 1 {
+                    let value = flags.trailing_zeros() + 1;
+                    return Some(SolveStep {
+                        technique: Technique::NakedSingle,
+                        houses: vec![],
+                        squares: vec![(x, y)],
+                        values: vec![value],
+                        placements: vec![Placement { x, y, value }],
+                        eliminations: vec![],
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn find_pointing(&self) -> Option<SolveStep> {
+        for box_index in 0..9 {
+            let house = House::Box(box_index);
+
+            for value in 1..=9 {
+                let positions = self.positions(house, value);
+                if positions.len() < 2 {
+                    continue;
+                }
+
+                let (first_x, first_y) = positions[0];
+                let line = if positions.iter().all(|&(_, y)| y == first_y) {
+                    House::Row(first_y)
+                } else if positions.iter().all(|&(x, _)| x == first_x) {
+                    House::Column(first_x)
+                } else {
+                    continue;
+                };
+
+                let outside_box = line
+                    .squares()
+                    .into_iter()
+                    .filter(|&(x, y)| House::box_containing(x, y) != house);
+                let eliminations = self.eliminations_of(value, outside_box);
+
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique: Technique::Pointing,
+                        houses: vec![house, line],
+                        squares: positions,
+                        values: vec![value],
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn find_claiming(&self) -> Option<SolveStep> {
+        let lines = (0..9).map(House::Row).chain((0..9).map(House::Column));
+
+        for line in lines {
+            for value in 1..=9 {
+                let positions = self.positions(line, value);
+                if positions.len() < 2 {
+                    continue;
+                }
+
+                let box_house = House::box_containing(positions[0].0, positions[0].1);
+                if positions.iter().any(|&(x, y)| House::box_containing(x, y) != box_house) {
+                    continue;
+                }
+
+                let outside_line = box_house
+                    .squares()
+                    .into_iter()
+                    .filter(|square| !line.squares().contains(square));
+                let eliminations = self.eliminations_of(value, outside_line);
+
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique: Technique::Claiming,
+                        houses: vec![line, box_house],
+                        squares: positions,
+                        values: vec![value],
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find `size` squares in a house that together only have `size`
+    /// candidates.
+    fn find_naked_subset(&self, technique: Technique, size: usize) -> Option<SolveStep> {
+        for house in House::all() {
+            let subset_squares = house
+                .squares()
+                .into_iter()
+                .filter(|&(x, y)| (2..=size as u32).contains(&self.candidate_flags(x, y).count_ones()))
+                .collect::<Vec<_>>();
+
+            for subset in subset_squares.into_iter().combinations(size) {
+                let flags = subset
+                    .iter()
+                    .fold(0, |flags, &(x, y)| flags | self.candidate_flags(x, y));
+                if flags.count_ones() as usize != size {
+                    continue;
+                }
+
+                let eliminations = house
+                    .squares()
+                    .into_iter()
+                    .filter(|square| !subset.contains(square))
+                    .flat_map(|(x, y)| flag_values(self.candidate_flags(x, y) & flags)
+                        .map(move |value| Elimination { x, y, value }))
+                    .collect::<Vec<_>>();
+
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique,
+                        houses: vec![house],
+                        squares: subset,
+                        values: flag_values(flags).collect(),
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find `size` values that can only go into the same `size` squares of a
+    /// house.
+    fn find_hidden_subset(&self, technique: Technique, size: usize) -> Option<SolveStep> {
+        for house in House::all() {
+            let subset_values = (1..=9)
+                .filter(|&value| (2..=size).contains(&self.positions(house, value).len()))
+                .collect::<Vec<_>>();
+
+            for subset in subset_values.into_iter().combinations(size) {
+                let squares = subset
+                    .iter()
+                    .flat_map(|&value| self.positions(house, value))
+                    .sorted_by_key(|&(x, y)| (y, x))
+                    .dedup()
+                    .collect::<Vec<_>>();
+                if squares.len() != size {
+                    continue;
+                }
+
+                let flags = subset.iter().fold(0, |flags, &value| flags | value_flag(value));
+                let eliminations = squares
+                    .iter()
+                    .flat_map(|&(x, y)| flag_values(self.candidate_flags(x, y) & !flags)
+                        .map(move |value| Elimination { x, y, value }))
+                    .collect::<Vec<_>>();
+
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique,
+                        houses: vec![house],
+                        squares,
+                        values: subset,
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find an X-Wing (`size` = 2), Swordfish (`size` = 3) or Jellyfish
+    /// (`size` = 4).
+    ///
+    /// The candidates for a value in `size` base lines lie in only `size`
+    /// cover lines crossing them, so the value can be removed from the rest of
+    /// the cover lines.
+    fn find_fish(&self, technique: Technique, size: usize) -> Option<SolveStep> {
+        type Line = fn(usize) -> House;
+        type LineIndex = fn((usize, usize)) -> usize;
+
+        // The base and cover lines plus how to get the index of the cover line
+        // from the coordinates of a square
+        let orientations: [(Line, Line, LineIndex); 2] = [
+            (House::Row, House::Column, |(x, _)| x),
+            (House::Column, House::Row, |(_, y)| y),
+        ];
+
+        for value in 1..=9 {
+            for (base_house, cover_house, cover_index) in orientations {
+                // The base lines together with the indices of the cover lines
+                // their candidates are in.
+                let base_lines = (0..9)
+                    .map(|index| {
+                        let cover_indices = self
+                            .positions(base_house(index), value)
+                            .into_iter()
+                            .map(cover_index)
+                            .collect::<Vec<_>>();
+                        (index, cover_indices)
+                    })
+                    .filter(|(_, cover_indices)| (2..=size).contains(&cover_indices.len()))
+                    .collect::<Vec<_>>();
+
+                for base in base_lines.iter().combinations(size) {
+                    let cover_indices = base
+                        .iter()
+                        .flat_map(|(_, cover_indices)| cover_indices.iter().copied())
+                        .sorted()
+                        .dedup()
+                        .collect::<Vec<_>>();
+                    if cover_indices.len() != size {
+                        continue;
+                    }
+
+                    let base_houses = base.iter().map(|(index, _)| base_house(*index)).collect::<Vec<_>>();
+                    let cover_houses = cover_indices.iter().map(|&index| cover_house(index)).collect::<Vec<_>>();
+
+                    let outside_base = cover_houses
+                        .iter()
+                        .flat_map(|cover| cover.squares())
+                        .filter(|square| !base_houses.iter().any(|base| base.squares().contains(square)));
+                    let eliminations = self.eliminations_of(value, outside_base);
+
+                    if !eliminations.is_empty() {
+                        let squares = base_houses
+                            .iter()
+                            .flat_map(|&base| self.positions(base, value))
+                            .collect();
+
+                        return Some(SolveStep {
+                            technique,
+                            houses: base_houses.into_iter().chain(cover_houses).collect(),
+                            squares,
+                            values: vec![value],
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn find_xy_wing(&self) -> Option<SolveStep> {
+        let bivalue_squares = self.squares_with_num_candidates(2);
+
+        for &pivot in &bivalue_squares {
+            let pivot_flags = self.candidate_flags(pivot.0, pivot.1);
+            let pincers = bivalue_squares
+                .iter()
+                .copied()
+                .filter(|&square| sees(square, pivot))
+                .filter(|&(x, y)| (self.candidate_flags(x, y) & pivot_flags).count_ones() == 1)
+                .collect::<Vec<_>>();
+
+            for (&pincer_0, &pincer_1) in pincers.iter().tuple_combinations() {
+                let flags_0 = self.candidate_flags(pincer_0.0, pincer_0.1);
+                let flags_1 = self.candidate_flags(pincer_1.0, pincer_1.1);
+
+                // The pincers must share the value Z that is not in the pivot
+                // and each contain a different value of the pivot
+                let z_flag = flags_0 & flags_1 & !pivot_flags;
+                if z_flag == 0 || flags_0 & flags_1 & pivot_flags != 0 {
+                    continue;
+                }
+
+                let z = z_flag.trailing_zeros() + 1;
+                if let Some(step) = self.wing_step(Technique::XYWing, z, &[pivot, pincer_0, pincer_1], &[pincer_0, pincer_1]) {
+                    return Some(step);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn find_xyz_wing(&self) -> Option<SolveStep> {
+        let bivalue_squares = self.squares_with_num_candidates(2);
+
+        for pivot in self.squares_with_num_candidates(3) {
+            let pivot_flags = self.candidate_flags(pivot.0, pivot.1);
+            let pincers = bivalue_squares
+                .iter()
+                .copied()
+                .filter(|&square| sees(square, pivot))
+                .filter(|&(x, y)| self.candidate_flags(x, y) & !pivot_flags == 0)
+                .collect::<Vec<_>>();
+
+            for (&pincer_0, &pincer_1) in pincers.iter().tuple_combinations() {
+                let flags_0 = self.candidate_flags(pincer_0.0, pincer_0.1);
+                let flags_1 = self.candidate_flags(pincer_1.0, pincer_1.1);
+
+                let z_flag = flags_0 & flags_1;
+                if z_flag.count_ones() != 1 || flags_0 | flags_1 != pivot_flags {
+                    continue;
+                }
+
+                let z = z_flag.trailing_zeros() + 1;
+                if let Some(step) = self.wing_step(Technique::XYZWing, z, &[pivot, pincer_0, pincer_1], &[pivot, pincer_0, pincer_1]) {
+                    return Some(step);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Build the step for a wing pattern made of `pattern`, eliminating `z`
+    /// from every square seeing all squares in `seen_by`.
+    fn wing_step(&self, technique: Technique, z: u32, pattern: &[(usize, usize)], seen_by: &[(usize, usize)]) -> Option<SolveStep> {
+        let targets = (0..9)
+            .cartesian_product(0..9)
+            .map(|(y, x)| (x, y))
+            .filter(|square| !pattern.contains(square))
+            .filter(|&square| seen_by.iter().all(|&other| sees(square, other)));
+        let eliminations = self.eliminations_of(z, targets);
+
+        if eliminations.is_empty() {
+            return None;
+        }
+
+        let values = pattern
+            .iter()
+            .fold(0, |flags, &(x, y)| flags | self.candidate_flags(x, y));
+
+        Some(SolveStep {
+            technique,
+            houses: vec![],
+            squares: pattern.to_vec(),
+            values: flag_values(values).collect(),
+            placements: vec![],
+            eliminations,
+        })
+    }
+
+    /// Get all squares with exactly `num_candidates` candidates.
+    fn squares_with_num_candidates(&self, num_candidates: u32) -> Vec<(usize, usize)> {
+        (0..9)
+            .cartesian_product(0..9)
+            .map(|(y, x)| (x, y))
+            .filter(|&(x, y)| self.candidate_flags(x, y).count_ones() == num_candidates)
+            .collect()
+    }
+}
+
+impl Iterator for LogicalSolver {
+    type Item = SolveStep;
+
+    fn next(&mut self) -> Option<SolveStep> {
+        self.step()
+    }
+}
+
+/// Get the candidate flag for a value.
+fn value_flag(value: u32) -> u16 {
+    1 << (value - 1)
+}
+
+/// Get the values whose flags are set, in ascending order.
+fn flag_values(flags: u16) -> impl Iterator<Item = u32> {
+    (1..=9).filter(move |&value| flags & value_flag(value) != 0)
+}
+
+/// Check if two different squares share a row, a column or a box.
+fn sees(a: (usize, usize), b: (usize, usize)) -> bool {
+    a != b && (a.0 == b.0 || a.1 == b.1 || House::box_containing(a.0, a.1) == House::box_containing(b.0, b.1))
+}
+
+/// Get all squares that share a row, a column or a box with the square at
+/// (`x` / `y`), not including the square itself.
+fn peers(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    House::Row(y).squares()
+        .into_iter()
+        .chain(House::Column(x).squares())
+        .chain(House::box_containing(x, y).squares())
+        .filter(move |&square| square != (x, y))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::House;
+    use crate::Sudoku;
+    use crate::tests::PUZZLE;
+
+    use super::{Elimination, LogicalSolver, Placement, Technique, ALL_CANDIDATES};
+
+    /// Puzzles that can be solved using the techniques of the
+    /// [LogicalSolver].
+    ///
+    /// Generated with http://www.opensky.ca/sudoku
+    const PUZZLES: [&str; 3] = [
+        "43...98..19.8....5...724....61.9..2...........2..5.69....637...8....5.31..51...76",
+        "7.6.......2...961....65...39..4352..8...9...5..3128..44...82....683...4.......5.1",
+        PUZZLE,
+    ];
+
+    /// Every placement of the logical solver must agree with the solution and
+    /// no elimination may remove the value of the solution.
+    #[test]
+    fn steps_agree_with_solution() {
+        let mut puzzles = PUZZLES.to_vec();
+        // the extremely difficult puzzle from the crate level tests
+        puzzles.push("94..5.8......34..9.......25159.7..4....5.6....6..4.59762.......3..72......4.8..32");
+
+        for puzzle in puzzles {
+            let sudoku: Sudoku = puzzle.parse().unwrap();
+            let solution = sudoku.find_solution().unwrap();
+
+            for step in LogicalSolver::new(&sudoku) {
+                assert!(!step.placements.is_empty() || !step.eliminations.is_empty());
+
+                for Placement { x, y, value } in step.placements {
+                    assert_eq!(solution.get_value(x, y), value, "{:?}", step.technique);
+                }
+                for Elimination { x, y, value } in step.eliminations {
+                    assert_ne!(solution.get_value(x, y), value, "{:?}", step.technique);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solves_simple_puzzles() {
+        for puzzle in PUZZLES {
+            let sudoku: Sudoku = puzzle.parse().unwrap();
+
+            let mut solver = LogicalSolver::new(&sudoku);
+            for _step in solver.by_ref() {}
+
+            assert_eq!(Some(*solver.sudoku()), sudoku.find_solution());
+        }
+    }
+
+    #[test]
+    fn no_steps_for_contradiction() {
+        // (2 / 0) can not contain any value
+        let sudoku: Sudoku = ("12.456789..3".to_owned() + &".".repeat(69)).parse().unwrap();
+        let solver = LogicalSolver::new(&sudoku);
+
+        assert!(solver.has_contradiction());
+        assert_eq!(solver.next_step(), None);
+    }
+
+    /// Build a solver for an empty grid where every square has all
+    /// candidates except for the ones listed in `removed`.
+    fn solver_without(removed: &[(usize, usize, u32)]) -> LogicalSolver {
+        let mut solver = LogicalSolver::new(&Sudoku::new_empty());
+        for &(x, y, value) in removed {
+            solver.candidates[x + y * 9] &= !super::value_flag(value);
+        }
+
+        solver
+    }
+
+    #[test]
+    fn hidden_single_in_box() {
+        let mut solver = solver_without(&[]);
+        for (x, y) in House::Box(4).squares() {
+            if (x, y) != (4, 5) {
+                solver.candidates[x + y * 9] &= !super::value_flag(6);
+            }
+        }
+
+        let step = solver.find(Technique::HiddenSingle).unwrap();
+
+        assert_eq!(step.houses, vec![House::Box(4)]);
+        assert_eq!(step.placements, vec![Placement { x: 4, y: 5, value: 6 }]);
+    }
+
+    #[test]
+    fn naked_single() {
+        let mut solver = solver_without(&[]);
+        solver.candidates[7 + 2 * 9] = super::value_flag(3);
+
+        let step = solver.find(Technique::NakedSingle).unwrap();
+
+        assert_eq!(step.placements, vec![Placement { x: 7, y: 2, value: 3 }]);
+    }
+
+    #[test]
+    fn pointing() {
+        // Remove 5 from the bottom row of the top left box, 5 is then
+        // restricted to the top two rows ...
+        let mut removed = vec![];
+        for x in 0..3 {
+            removed.push((x, 2, 5));
+            removed.push((x, 1, 5));
+        }
+        // ... and then just to the top row
+        let solver = solver_without(&removed);
+
+        let step = solver.find(Technique::Pointing).unwrap();
+
+        assert_eq!(step.houses, vec![House::Box(0), House::Row(0)]);
+        assert_eq!(step.eliminations.len(), 6);
+        assert!(step.eliminations.iter().all(|elimination| elimination.y == 0 && elimination.x >= 3 && elimination.value == 5));
+    }
+
+    #[test]
+    fn naked_pair() {
+        let mut solver = solver_without(&[]);
+        solver.candidates[2 + 4 * 9] = ALL_CANDIDATES & (super::value_flag(1) | super::value_flag(9));
+        solver.candidates[6 + 4 * 9] = ALL_CANDIDATES & (super::value_flag(1) | super::value_flag(9));
+
+        let step = solver.find(Technique::NakedPair).unwrap();
+
+        assert_eq!(step.houses, vec![House::Row(4)]);
+        assert_eq!(step.values, vec![1, 9]);
+        // 7 squares in the row lose 2 candidates each
+        assert_eq!(step.eliminations.len(), 14);
+    }
+
+    #[test]
+    fn hidden_pair() {
+        let mut removed = vec![];
+        for y in 0..9 {
+            if y != 1 && y != 7 {
+                removed.push((3, y, 2));
+                removed.push((3, y, 8));
+            }
+        }
+        let solver = solver_without(&removed);
+
+        let step = solver.find(Technique::HiddenPair).unwrap();
+
+        assert_eq!(step.houses, vec![House::Column(3)]);
+        assert_eq!(step.squares, vec![(3, 1), (3, 7)]);
+        assert_eq!(step.values, vec![2, 8]);
+        assert_eq!(step.eliminations.len(), 14);
+    }
+
+    #[test]
+    fn x_wing() {
+        // 4 can only go into columns 1 and 7 in rows 2 and 6
+        let mut removed = vec![];
+        for x in 0..9 {
+            if x != 1 && x != 7 {
+                removed.push((x, 2, 4));
+                removed.push((x, 6, 4));
+            }
+        }
+        let solver = solver_without(&removed);
+
+        let step = solver.find(Technique::XWing).unwrap();
+
+        assert_eq!(step.houses, vec![House::Row(2), House::Row(6), House::Column(1), House::Column(7)]);
+        assert_eq!(step.squares, vec![(1, 2), (7, 2), (1, 6), (7, 6)]);
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(step.eliminations.iter().all(|elimination| elimination.value == 4));
+    }
+
+    #[test]
+    fn xy_wing() {
+        let mut solver = solver_without(&[]);
+        // pivot {1, 2} at (0, 0), pincers {1, 3} at (5, 0) and {2, 3} at (0, 5)
+        solver.candidates[0] = super::value_flag(1) | super::value_flag(2);
+        solver.candidates[5] = super::value_flag(1) | super::value_flag(3);
+        solver.candidates[5 * 9] = super::value_flag(2) | super::value_flag(3);
+
+        let step = solver.find(Technique::XYWing).unwrap();
+
+        assert_eq!(step.squares, vec![(0, 0), (5, 0), (0, 5)]);
+        assert_eq!(step.eliminations, vec![Elimination { x: 5, y: 5, value: 3 }]);
+    }
+}
@@ -0,0 +1,777 @@
+//! The `sudoku` command line tool.
+//!
+//! Run `sudoku help` for a list of all subcommands.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use serde::{Serialize, Serializer};
+
+use sudoku::{ParseSudokuError, SolutionCount, Sudoku, SudokuError};
+use sudoku::generator::{Generator, Symmetry, DEFAULT_MAX_ATTEMPTS};
+use sudoku::rating::{Difficulty, Rating};
+use sudoku::solver::{Engine, Solver};
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// How many lines are read before they are checked and written. Checking
+/// happens in batches so the output does not have to wait for the whole
+/// input, but the threads still have enough work to share.
+const BATCH_SIZE: usize = 4096;
+
+/// The exit code used if at least one puzzle did not pass the check of the
+/// subcommand.
+const EXIT_REJECTED: u8 = 3;
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  Every puzzle passed
+  1  An error occurred, e.g. the input could not be read
+  2  The command line arguments are invalid
+  3  At least one puzzle did not pass, see the help of the subcommand";
+
+/// Solve, generate and check Sudoku puzzles from the command line.
+///
+/// Puzzles are read one per line, as 81 characters with digits for clues and
+/// any of `0`, `.`, `_` or `*` for empty squares. Whitespace is ignored and
+/// empty lines are skipped. For every puzzle, one result is written.
+#[derive(Debug, Parser)]
+#[command(version, after_help = EXIT_CODES)]
+struct Cli {
+    /// Write one JSON object per line instead of plain text.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Solve puzzles.
+    ///
+    /// Writes the solution of every puzzle, or "no solution", "multiple
+    /// solutions" or "invalid puzzle". Exits with code 3 unless every puzzle
+    /// has exactly one solution.
+    #[command(after_help = EXIT_CODES)]
+    Solve(SolveArgs),
+
+    /// Generate random puzzles with a unique solution.
+    ///
+    /// Exits with code 3 if no puzzle of the requested difficulty was found.
+    #[command(after_help = EXIT_CODES)]
+    Generate(GenerateArgs),
+
+    /// Rate the difficulty of puzzles.
+    ///
+    /// The difficulty depends on the hardest solving technique a human needs.
+    /// Exits with code 3 if any puzzle has no solution.
+    #[command(after_help = EXIT_CODES)]
+    Rate(InputArgs),
+
+    /// Check that puzzles are proper puzzles.
+    ///
+    /// A puzzle is valid if no value appears twice in a row, column or box and
+    /// it has exactly one solution. Exits with code 3 if any puzzle is not
+    /// valid.
+    #[command(after_help = EXIT_CODES)]
+    Validate(InputArgs),
+
+    /// Count the solutions of puzzles.
+    ///
+    /// Exits with code 3 if any line is not a puzzle at all.
+    #[command(after_help = EXIT_CODES)]
+    Count(CountArgs),
+}
+
+/// The arguments of all subcommands reading puzzles.
+#[derive(Args, Debug)]
+struct InputArgs {
+    /// The file to read the puzzles from. Reads from stdin if missing or `-`.
+    input: Option<PathBuf>,
+
+    /// The number of threads checking puzzles, defaults to the number of CPU
+    /// cores.
+    #[arg(short, long)]
+    threads: Option<NonZeroUsize>,
+}
+
+#[derive(Args, Debug)]
+struct SolveArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// How to write solutions.
+    #[arg(short, long, value_enum, default_value_t = Format::Line)]
+    format: Format,
+
+    /// The engine used for solving.
+    #[arg(short, long, value_enum, default_value_t = EngineArg::Bitboard)]
+    engine: EngineArg,
+}
+
+#[derive(Args, Debug)]
+struct GenerateArgs {
+    /// The number of puzzles to generate.
+    #[arg(short = 'n', long, default_value_t = 1)]
+    count: usize,
+
+    /// Stop removing clues once a puzzle has this many. Puzzles may end up
+    /// with more clues if no more can be removed.
+    #[arg(short, long)]
+    clues: Option<usize>,
+
+    /// The symmetry of the layout of the clues.
+    #[arg(short, long, value_enum, default_value_t = SymmetryArg::None)]
+    symmetry: SymmetryArg,
+
+    /// Only write puzzles of this difficulty.
+    #[arg(short, long, value_enum)]
+    difficulty: Option<DifficultyArg>,
+
+    /// How many puzzles may be tried for each puzzle of the requested
+    /// difficulty.
+    #[arg(long, default_value_t = DEFAULT_MAX_ATTEMPTS)]
+    max_attempts: usize,
+
+    /// Seed the random number generator to always generate the same puzzles.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// How to write puzzles.
+    #[arg(short, long, value_enum, default_value_t = Format::Line)]
+    format: Format,
+}
+
+#[derive(Args, Debug)]
+struct CountArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Stop counting after this many solutions.
+    #[arg(short, long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    limit: Option<usize>,
+
+    /// The engine used for solving.
+    #[arg(short, long, value_enum, default_value_t = EngineArg::Bitboard)]
+    engine: EngineArg,
+}
+
+/// The output format of a [Sudoku].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum Format {
+    /// 81 digits on one line.
+    Line,
+    /// 9 lines of 9 digits, followed by an empty line.
+    Grid,
+}
+
+/// The command line names of [Engine]s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum EngineArg {
+    Bitboard,
+    Backtracking,
+    DancingLinks,
+}
+
+impl From<EngineArg> for Engine {
+    fn from(engine: EngineArg) -> Engine {
+        match engine {
+            EngineArg::Bitboard => Engine::Bitboard,
+            EngineArg::Backtracking => Engine::Backtracking,
+            EngineArg::DancingLinks => Engine::DancingLinks,
+        }
+    }
+}
+
+/// The command line names of [Symmetry]s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum SymmetryArg {
+    None,
+    Rotational180,
+    Rotational90,
+    HorizontalMirror,
+    VerticalMirror,
+    Diagonal,
+    AntiDiagonal,
+}
+
+impl From<SymmetryArg> for Symmetry {
+    fn from(symmetry: SymmetryArg) -> Symmetry {
+        match symmetry {
+            SymmetryArg::None => Symmetry::None,
+            SymmetryArg::Rotational180 => Symmetry::Rotational180,
+            SymmetryArg::Rotational90 => Symmetry::Rotational90,
+            SymmetryArg::HorizontalMirror => Symmetry::HorizontalMirror,
+            SymmetryArg::VerticalMirror => Symmetry::VerticalMirror,
+            SymmetryArg::Diagonal => Symmetry::Diagonal,
+            SymmetryArg::AntiDiagonal => Symmetry::AntiDiagonal,
+        }
+    }
+}
+
+/// The command line names of [Difficulty]s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum DifficultyArg {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    RequiresGuessing,
+}
+
+impl From<DifficultyArg> for Difficulty {
+    fn from(difficulty: DifficultyArg) -> Difficulty {
+        match difficulty {
+            DifficultyArg::Easy => Difficulty::Easy,
+            DifficultyArg::Medium => Difficulty::Medium,
+            DifficultyArg::Hard => Difficulty::Hard,
+            DifficultyArg::Expert => Difficulty::Expert,
+            DifficultyArg::RequiresGuessing => Difficulty::RequiresGuessing,
+        }
+    }
+}
+
+/// The result of checking one puzzle.
+trait Report: Serialize + Send {
+
+    /// Whether the puzzle passed the check, see [EXIT_CODES].
+    fn passed(&self) -> bool;
+}
+
+/// The result of solving one puzzle.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum SolveReport {
+    Solved {
+        solution: Box<Sudoku>,
+    },
+    NoSolution,
+    MultipleSolutions,
+    Invalid {
+        #[serde(serialize_with = "serialize_display")]
+        error: ParseSudokuError,
+    },
+}
+
+impl Report for SolveReport {
+    fn passed(&self) -> bool {
+        matches!(self, SolveReport::Solved { .. })
+    }
+}
+
+/// The result of rating one puzzle.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum RateReport {
+    Rated(Rating),
+    NoSolution,
+    Invalid {
+        #[serde(serialize_with = "serialize_display")]
+        error: ParseSudokuError,
+    },
+}
+
+impl Report for RateReport {
+    fn passed(&self) -> bool {
+        matches!(self, RateReport::Rated(_))
+    }
+}
+
+/// The result of validating one puzzle.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ValidateReport {
+    Valid,
+    /// The same value appears twice in a house, see
+    /// [SudokuError::ConflictingGivens].
+    Conflict {
+        value: u32,
+        first: (usize, usize),
+        second: (usize, usize),
+    },
+    NoSolution,
+    MultipleSolutions,
+    Invalid {
+        #[serde(serialize_with = "serialize_display")]
+        error: ParseSudokuError,
+    },
+}
+
+impl Report for ValidateReport {
+    fn passed(&self) -> bool {
+        *self == ValidateReport::Valid
+    }
+}
+
+/// The result of counting the solutions of one puzzle.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum CountReport {
+    Counted {
+        solutions: usize,
+        /// False if there are more solutions than the limit, so `solutions`
+        /// is only a lower bound.
+        complete: bool,
+    },
+    Invalid {
+        #[serde(serialize_with = "serialize_display")]
+        error: ParseSudokuError,
+    },
+}
+
+impl Report for CountReport {
+    fn passed(&self) -> bool {
+        matches!(self, CountReport::Counted { .. })
+    }
+}
+
+/// A puzzle written by the `generate` subcommand.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct GenerateReport {
+    puzzle: Sudoku,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<Rating>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Solve(args) => solve(args, cli.json),
+        Command::Generate(args) => generate(args, cli.json),
+        Command::Rate(args) => rate(args, cli.json),
+        Command::Validate(args) => validate(args, cli.json),
+        Command::Count(args) => count(args, cli.json),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(EXIT_REJECTED),
+        Err(error) => {
+            eprintln!("sudoku: {}", error);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/// Run the `solve` subcommand.
+fn solve(args: SolveArgs, json: bool) -> io::Result<bool> {
+    let solver = Solver::new().engine(args.engine.into());
+
+    check_puzzles(args.input, json, |line| solve_line(line, solver), |output, report| {
+        match report {
+            SolveReport::Solved { solution } => write_sudoku(output, solution, args.format),
+            SolveReport::NoSolution => write_message(output, "no solution", args.format),
+            SolveReport::MultipleSolutions => write_message(output, "multiple solutions", args.format),
+            SolveReport::Invalid { error } => write_message(output, &format!("invalid puzzle: {}", error), args.format),
+        }
+    })
+}
+
+/// Run the `generate` subcommand.
+fn generate(args: GenerateArgs, json: bool) -> io::Result<bool> {
+    let mut generator = match args.seed {
+        Some(seed) => Generator::with_seed(seed),
+        None => Generator::new(),
+    };
+    generator = generator.symmetry(args.symmetry.into()).max_attempts(args.max_attempts);
+    if let Some(clues) = args.clues {
+        generator = generator.target_clues(clues);
+    }
+
+    let mut output = BufWriter::new(io::stdout().lock());
+
+    for _ in 0..args.count {
+        let report = match args.difficulty.map(Difficulty::from) {
+            Some(difficulty) => match generator.generate_with_difficulty(difficulty..=difficulty) {
+                Some(generated) => GenerateReport {
+                    puzzle: generated.puzzle,
+                    rating: Some(generated.rating),
+                },
+                None => {
+                    output.flush()?;
+                    eprintln!("sudoku: no {} puzzle found within {} attempts", difficulty, args.max_attempts);
+                    return Ok(false);
+                },
+            },
+            None => GenerateReport {
+                puzzle: generator.generate(),
+                rating: None,
+            },
+        };
+
+        if json {
+            write_json(&mut output, &report)?;
+        } else {
+            write_sudoku(&mut output, &report.puzzle, args.format)?;
+        }
+    }
+
+    output.flush()?;
+
+    Ok(true)
+}
+
+/// Run the `rate` subcommand.
+fn rate(args: InputArgs, json: bool) -> io::Result<bool> {
+    check_puzzles(args, json, rate_line, |output, report| {
+        match report {
+            RateReport::Rated(rating) => writeln!(output, "{} ({:.1})", rating.difficulty, rating.score),
+            RateReport::NoSolution => writeln!(output, "no solution"),
+            RateReport::Invalid { error } => writeln!(output, "invalid puzzle: {}", error),
+        }
+    })
+}
+
+/// Run the `validate` subcommand.
+fn validate(args: InputArgs, json: bool) -> io::Result<bool> {
+    check_puzzles(args, json, validate_line, |output, report| {
+        match *report {
+            ValidateReport::Valid => writeln!(output, "valid"),
+            ValidateReport::Conflict { value, first, second } => {
+                writeln!(output, "conflict: {}", SudokuError::ConflictingGivens { value, first, second })
+            },
+            ValidateReport::NoSolution => writeln!(output, "no solution"),
+            ValidateReport::MultipleSolutions => writeln!(output, "multiple solutions"),
+            ValidateReport::Invalid { ref error } => writeln!(output, "invalid puzzle: {}", error),
+        }
+    })
+}
+
+/// Run the `count` subcommand.
+fn count(args: CountArgs, json: bool) -> io::Result<bool> {
+    let solver = Solver::new().engine(args.engine.into());
+
+    check_puzzles(args.input, json, |line| count_line(line, solver, args.limit), |output, report| {
+        match report {
+            CountReport::Counted { solutions, complete: true } => writeln!(output, "{}", solutions),
+            CountReport::Counted { solutions, complete: false } => writeln!(output, "at least {}", solutions),
+            CountReport::Invalid { error } => writeln!(output, "invalid puzzle: {}", error),
+        }
+    })
+}
+
+/// Check every puzzle read from `input` and write one report per puzzle,
+/// either as JSON or using `write_text`.
+///
+/// Return whether every puzzle passed the check.
+fn check_puzzles<R: Report>(
+    input: InputArgs,
+    json: bool,
+    check: impl Fn(&str) -> R + Sync,
+    write_text: impl Fn(&mut dyn Write, &R) -> io::Result<()>,
+) -> io::Result<bool> {
+    let threads = input.threads.map_or_else(default_threads, NonZeroUsize::get);
+    let reader = open_input(input.input)?;
+    let mut output = BufWriter::new(io::stdout().lock());
+    let mut all_passed = true;
+
+    let mut lines = reader.lines();
+    loop {
+        let batch = next_batch(&mut lines)?;
+        if batch.is_empty() {
+            break;
+        }
+
+        for report in check_all(&batch, threads, &check) {
+            all_passed &= report.passed();
+
+            if json {
+                write_json(&mut output, &report)?;
+            } else {
+                write_text(&mut output, &report)?;
+            }
+        }
+    }
+
+    output.flush()?;
+
+    Ok(all_passed)
+}
+
+/// Read up to [BATCH_SIZE] lines that are not blank from `lines`.
+///
+/// Return an empty batch only once `lines` is exhausted.
+fn next_batch(lines: &mut impl Iterator<Item = io::Result<String>>) -> io::Result<Vec<String>> {
+    lines
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .take(BATCH_SIZE)
+        .collect()
+}
+
+/// Open the file at `path` for reading, or stdin if there is no path or the
+/// path is `-`.
+fn open_input(path: Option<PathBuf>) -> io::Result<Box<dyn BufRead>> {
+    match path {
+        Some(path) if path.as_os_str() != "-" => {
+            let file = File::open(&path)
+                .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path.display(), error)))?;
+
+            Ok(Box::new(BufReader::new(file)))
+        },
+        _ => Ok(Box::new(io::stdin().lock())),
+    }
+}
+
+/// Get the number of CPU cores.
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Check every line of `lines` using `threads` threads and return the
+/// reports in the same order.
+fn check_all<R: Send>(lines: &[String], threads: usize, check: &(impl Fn(&str) -> R + Sync)) -> Vec<R> {
+    let next_line = AtomicUsize::new(0);
+
+    let mut reports = thread::scope(|scope| {
+        let workers = (0..threads.min(lines.len()))
+            .map(|_| scope.spawn(|| {
+                let mut reports = Vec::new();
+                loop {
+                    let index = next_line.fetch_add(1, Ordering::Relaxed);
+                    match lines.get(index) {
+                        Some(line) => reports.push((index, check(line))),
+                        None => return reports,
+                    }
+                }
+            }))
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("worker thread panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    reports.sort_unstable_by_key(|&(index, _)| index);
+
+    reports.into_iter().map(|(_, report)| report).collect()
+}
+
+/// Parse and solve a single puzzle.
+fn solve_line(line: &str, solver: Solver) -> SolveReport {
+    let sudoku = match Sudoku::parse_line(line) {
+        Ok(sudoku) => sudoku,
+        Err(error) => return SolveReport::Invalid { error },
+    };
+
+    let mut solutions = solver.find_all_solutions(&sudoku);
+
+    match (solutions.next(), solutions.next()) {
+        (None, _) => SolveReport::NoSolution,
+        (Some(solution), None) => SolveReport::Solved { solution: Box::new(solution) },
+        (Some(_), Some(_)) => SolveReport::MultipleSolutions,
+    }
+}
+
+/// Parse and rate a single puzzle.
+fn rate_line(line: &str) -> RateReport {
+    match Sudoku::parse_line(line) {
+        Ok(sudoku) => sudoku.rate().map_or(RateReport::NoSolution, RateReport::Rated),
+        Err(error) => RateReport::Invalid { error },
+    }
+}
+
+/// Parse and validate a single puzzle.
+fn validate_line(line: &str) -> ValidateReport {
+    let sudoku = match Sudoku::parse_line(line) {
+        Ok(sudoku) => sudoku,
+        Err(error) => return ValidateReport::Invalid { error },
+    };
+
+    if let Err(SudokuError::ConflictingGivens { value, first, second }) = sudoku.validate() {
+        return ValidateReport::Conflict { value, first, second };
+    }
+
+    match sudoku.count_solutions(2) {
+        SolutionCount::None => ValidateReport::NoSolution,
+        SolutionCount::Unique => ValidateReport::Valid,
+        SolutionCount::Multiple(_) => ValidateReport::MultipleSolutions,
+    }
+}
+
+/// Parse a single puzzle and count its solutions, up to `limit` if given.
+fn count_line(line: &str, solver: Solver, limit: Option<usize>) -> CountReport {
+    let sudoku = match Sudoku::parse_line(line) {
+        Ok(sudoku) => sudoku,
+        Err(error) => return CountReport::Invalid { error },
+    };
+
+    let solutions = solver.find_all_solutions(&sudoku);
+    let (solutions, complete) = match limit {
+        Some(limit) => {
+            // Looking for one solution more tells if the count is exact.
+            let solutions = solutions.take(limit.saturating_add(1)).count();
+            (solutions.min(limit), solutions <= limit)
+        },
+        None => (solutions.count(), true),
+    };
+
+    CountReport::Counted { solutions, complete }
+}
+
+/// Write `sudoku` in the given [Format].
+fn write_sudoku(output: &mut dyn Write, sudoku: &Sudoku, format: Format) -> io::Result<()> {
+    match format {
+        Format::Line => writeln!(output, "{}", sudoku.line_repr()),
+        Format::Grid => writeln!(output, "{}", sudoku.string_repr()),
+    }
+}
+
+/// Write `message` instead of a [Sudoku].
+fn write_message(output: &mut dyn Write, message: &str, format: Format) -> io::Result<()> {
+    match format {
+        Format::Line => writeln!(output, "{}", message),
+        Format::Grid => writeln!(output, "{}\n", message),
+    }
+}
+
+/// Write `report` as JSON on a single line.
+fn write_json(output: &mut dyn Write, report: &impl Serialize) -> io::Result<()> {
+    serde_json::to_writer(&mut *output, report)?;
+    writeln!(output)
+}
+
+/// Serialize `value` as a string using its [fmt::Display] implementation.
+fn serialize_display<S: Serializer>(value: &impl fmt::Display, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use clap::CommandFactory;
+
+    use super::*;
+
+    // Values generated with http://www.opensky.ca/sudoku
+    const PUZZLE: &str = "..1.2.9..9...4..2..2..98.51.17......4..7.6..9......61.13.87..6..7..5...4..5.6.3..";
+
+    // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+    const THREE_SOLUTIONS: &str = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4";
+
+    fn json(report: &impl Serialize) -> String {
+        let mut output = Vec::new();
+        write_json(&mut output, report).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn cli_is_well_formed() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn batches_skip_blank_lines() {
+        let input = "\n".repeat(BATCH_SIZE + 1) + PUZZLE + "\n \n" + PUZZLE;
+        let mut lines = input.as_bytes().lines();
+
+        assert_eq!(next_batch(&mut lines).unwrap(), vec![PUZZLE, PUZZLE]);
+        assert!(next_batch(&mut lines).unwrap().is_empty());
+    }
+
+    #[test]
+    fn count_limit_is_positive() {
+        assert!(Cli::try_parse_from(["sudoku", "count", "--limit", "0"]).is_err());
+        assert!(Cli::try_parse_from(["sudoku", "count", "--limit", "1"]).is_ok());
+    }
+
+    #[test]
+    fn solve_line_reports() {
+        let solution = Sudoku::parse_line(PUZZLE).unwrap().find_solution().unwrap();
+        assert_eq!(solve_line(PUZZLE, Solver::new()), SolveReport::Solved { solution: Box::new(solution) });
+
+        assert_eq!(solve_line(&".".repeat(81), Solver::new()), SolveReport::MultipleSolutions);
+        assert_eq!(solve_line(&("11".to_owned() + &".".repeat(79)), Solver::new()), SolveReport::NoSolution);
+        assert!(matches!(solve_line("12345", Solver::new()), SolveReport::Invalid { .. }));
+    }
+
+    #[test]
+    fn check_all_keeps_order() {
+        let lines = [PUZZLE, "1", &".".repeat(81)].repeat(20).into_iter().map(String::from).collect::<Vec<_>>();
+        let check = |line: &str| solve_line(line, Solver::new());
+
+        let sequential = check_all(&lines, 1, &check);
+        let parallel = check_all(&lines, 4, &check);
+
+        assert_eq!(sequential, parallel);
+        assert_eq!(sequential.len(), lines.len());
+        assert_eq!(sequential[2], SolveReport::MultipleSolutions);
+    }
+
+    #[test]
+    fn rate_line_reports() {
+        let rated = rate_line(PUZZLE);
+        assert_eq!(rated, RateReport::Rated(Sudoku::parse_line(PUZZLE).unwrap().rate().unwrap()));
+        assert!(matches!(rated, RateReport::Rated(rating) if rating.difficulty == Difficulty::Medium));
+
+        assert_eq!(rate_line(&("11".to_owned() + &".".repeat(79))), RateReport::NoSolution);
+        assert!(!rate_line("12345").passed());
+    }
+
+    #[test]
+    fn validate_line_reports() {
+        assert_eq!(validate_line(PUZZLE), ValidateReport::Valid);
+        assert_eq!(validate_line(THREE_SOLUTIONS), ValidateReport::MultipleSolutions);
+        assert_eq!(validate_line(&("11".to_owned() + &".".repeat(79))),
+                   ValidateReport::Conflict { value: 1, first: (0, 0), second: (1, 0) });
+        assert_eq!(validate_line(&("12.456789..3".to_owned() + &".".repeat(69))), ValidateReport::NoSolution);
+        assert!(matches!(validate_line("12345"), ValidateReport::Invalid { .. }));
+    }
+
+    #[test]
+    fn count_line_reports() {
+        for engine in Engine::ALL {
+            let solver = Solver::new().engine(engine);
+
+            assert_eq!(count_line(THREE_SOLUTIONS, solver, None), CountReport::Counted { solutions: 3, complete: true });
+            assert_eq!(count_line(PUZZLE, solver, Some(1)), CountReport::Counted { solutions: 1, complete: true });
+            assert_eq!(count_line(THREE_SOLUTIONS, solver, Some(2)), CountReport::Counted { solutions: 2, complete: false });
+            assert_eq!(count_line(THREE_SOLUTIONS, solver, Some(3)), CountReport::Counted { solutions: 3, complete: true });
+            assert_eq!(count_line(THREE_SOLUTIONS, solver, Some(5)), CountReport::Counted { solutions: 3, complete: true });
+            assert_eq!(count_line(&".".repeat(81), solver, Some(10)), CountReport::Counted { solutions: 10, complete: false });
+        }
+    }
+
+    #[test]
+    fn text_formats() {
+        let solution = Sudoku::parse_line(PUZZLE).unwrap().find_solution().unwrap();
+
+        let mut line = Vec::new();
+        write_sudoku(&mut line, &solution, Format::Line).unwrap();
+        write_message(&mut line, "no solution", Format::Line).unwrap();
+        assert_eq!(String::from_utf8(line).unwrap(), format!("{}\nno solution\n", solution.line_repr()));
+
+        let mut grid = Vec::new();
+        write_sudoku(&mut grid, &solution, Format::Grid).unwrap();
+        write_message(&mut grid, "multiple solutions", Format::Grid).unwrap();
+        assert_eq!(String::from_utf8(grid).unwrap(), format!("{}\nmultiple solutions\n\n", solution.string_repr()));
+    }
+
+    #[test]
+    fn json_reports() {
+        let solution = Sudoku::parse_line(PUZZLE).unwrap().find_solution().unwrap();
+        assert_eq!(json(&SolveReport::Solved { solution: Box::new(solution) }),
+                   format!("{{\"status\":\"solved\",\"solution\":\"{}\"}}\n", solution.line_repr()));
+        assert_eq!(json(&SolveReport::MultipleSolutions), "{\"status\":\"multiple_solutions\"}\n");
+        assert_eq!(json(&solve_line("12345", Solver::new())),
+                   format!("{{\"status\":\"invalid\",\"error\":\"{}\"}}\n", Sudoku::parse_line("12345").unwrap_err()));
+
+        assert_eq!(json(&ValidateReport::Conflict { value: 1, first: (0, 0), second: (1, 0) }),
+                   "{\"status\":\"conflict\",\"value\":1,\"first\":[0,0],\"second\":[1,0]}\n");
+        assert_eq!(json(&CountReport::Counted { solutions: 3, complete: true }),
+                   "{\"status\":\"counted\",\"solutions\":3,\"complete\":true}\n");
+
+        let rated = json(&rate_line(PUZZLE));
+        assert!(rated.starts_with("{\"status\":\"rated\",\"difficulty\":\"medium\",\"score\":"), "{}", rated);
+        assert!(rated.contains("\"hardest_technique\":\"claiming\""), "{}", rated);
+    }
+}
@@ -0,0 +1,474 @@
+//! Enumerating the solutions of a [Sudoku] on multiple threads.
+//!
+//! The search tree is split at its first few branch points into independent
+//! sub-puzzles, which are then solved by a pool of worker threads.
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::parallel::ParallelSolver;
+//!
+//! // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+//! let sudoku: Sudoku = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4".parse().unwrap();
+//!
+//! let solver = ParallelSolver::new().threads(4);
+//!
+//! assert_eq!(solver.count_solutions(&sudoku), 3);
+//! ```
+
+use crate::{Sudoku, NUM_SQUARES};
+use crate::solver::{Branch, Branching, Solver};
+
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroUsize;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// How many sub-puzzles are created per thread, so that threads that got
+/// easy sub-puzzles can help out with the rest.
+const TASKS_PER_THREAD: usize = 16;
+
+/// How many sub-puzzles are created when the solutions are returned in order.
+/// This does not depend on the number of threads, since the order of the
+/// solutions depends on how the search is split.
+const ORDERED_TASKS: usize = 256;
+
+/// How many solutions can be waiting to be returned by [ParallelSolutions]
+/// before the workers have to wait.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Finds the solutions of [Sudoku]s using multiple threads.
+///
+/// Every thread uses a [Solver], which can be configured with
+/// [ParallelSolver::solver()].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParallelSolver {
+    solver: Solver,
+    threads: usize,
+    ordered: bool,
+}
+
+impl ParallelSolver {
+
+    /// Initialize a new [ParallelSolver] using one thread for every CPU core
+    /// and the default [Solver].
+    pub fn new() -> ParallelSolver {
+        ParallelSolver {
+            solver: Solver::new(),
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            ordered: false,
+        }
+    }
+
+    /// Set the [Solver] used by every thread.
+    pub fn solver(mut self, solver: Solver) -> ParallelSolver {
+        self.solver = solver;
+        self
+    }
+
+    /// Set the number of worker threads. Values smaller than 1 are treated
+    /// like 1.
+    pub fn threads(mut self, threads: usize) -> ParallelSolver {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Set whether [ParallelSolver::find_all_solutions()] returns the
+    /// solutions in a deterministic order.
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::parallel::ParallelSolver;
+    ///
+    /// let sudoku = Sudoku::new_empty();
+    ///
+    /// let first = ParallelSolver::new().threads(2).ordered(true).find_all_solutions(&sudoku).take(100).collect::<Vec<_>>();
+    /// let second = ParallelSolver::new().threads(8).ordered(true).find_all_solutions(&sudoku).take(100).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(first, second);
+    /// ```
+    ///
+    /// The search is split into the same sub-puzzles for any number of
+    /// threads, and the solutions of every sub-puzzle are returned in the
+    /// order of the [Solver], so the order is the same for any number of
+    /// threads. It is not the order of [Solver::find_all_solutions()] though.
+    /// Solutions found ahead of their turn are kept in memory until it is
+    /// their turn.
+    ///
+    /// The default is `false`, which returns solutions as soon as they are
+    /// found.
+    pub fn ordered(mut self, ordered: bool) -> ParallelSolver {
+        self.ordered = ordered;
+        self
+    }
+
+    /// Count all solutions of `sudoku`.
+    ///
+    /// Unlike [Solver::count_solutions()], there is no limit, so make sure
+    /// the number of solutions is not astronomically large.
+    pub fn count_solutions(&self, sudoku: &Sudoku) -> usize {
+        let tasks = split(sudoku, self.threads * TASKS_PER_THREAD);
+        let next_task = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            let workers = (0..self.threads)
+                .map(|_| scope.spawn(|| {
+                    let mut count = 0;
+                    while let Some(task) = tasks.get(next_task.fetch_add(1, Ordering::Relaxed)) {
+                        count += self.solver.find_all_solutions(task).count();
+                    }
+                    count
+                }))
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|payload| panic::resume_unwind(payload)))
+                .sum()
+        })
+    }
+
+    /// Find all solutions of `sudoku`.
+    ///
+    /// The worker threads start immediately and run in the background while
+    /// the returned [ParallelSolutions] is being iterated. Dropping it stops
+    /// them.
+    ///
+    /// If a worker thread panics, iterating the [ParallelSolutions] panics
+    /// with the same payload once the other workers are done, rather than
+    /// leaving out solutions.
+    pub fn find_all_solutions(&self, sudoku: &Sudoku) -> ParallelSolutions {
+        let target = if self.ordered { ORDERED_TASKS } else { self.threads * TASKS_PER_THREAD };
+        let tasks = Arc::new(split(sudoku, target));
+        let next_task = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+
+        let workers = (0..self.threads)
+            .map(|_| {
+                let (tasks, next_task, sender, solver) = (Arc::clone(&tasks), Arc::clone(&next_task), sender.clone(), self.solver);
+
+                thread::spawn(move || work(&tasks, &next_task, &sender, solver))
+            })
+            .collect();
+
+        ParallelSolutions {
+            receiver,
+            workers,
+            ordered: self.ordered.then(|| Ordered {
+                current: 0,
+                num_tasks: tasks.len(),
+                pending: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl Default for ParallelSolver {
+    fn default() -> ParallelSolver {
+        ParallelSolver::new()
+    }
+}
+
+/// A message from a worker thread: a solution of a task, or `None` once all
+/// solutions of the task were sent.
+type Message = (usize, Option<Sudoku>);
+
+/// Solve tasks until there are none left or nobody is listening anymore.
+fn work(tasks: &[Sudoku], next_task: &AtomicUsize, sender: &SyncSender<Message>, solver: Solver) {
+    loop {
+        let index = next_task.fetch_add(1, Ordering::Relaxed);
+        let task = match tasks.get(index) {
+            Some(task) => task,
+            None => return,
+        };
+
+        for solution in solver.find_all_solutions(task) {
+            if sender.send((index, Some(solution))).is_err() {
+                return;
+            }
+        }
+
+        if sender.send((index, None)).is_err() {
+            return;
+        }
+    }
+}
+
+/// The [Iterator] returned by [ParallelSolver::find_all_solutions()].
+#[derive(Debug)]
+pub struct ParallelSolutions {
+    receiver: Receiver<Message>,
+    workers: Vec<JoinHandle<()>>,
+    ordered: Option<Ordered>,
+}
+
+impl ParallelSolutions {
+
+    /// Receive the next message from the workers, or `None` once all of them
+    /// are done.
+    ///
+    /// Resumes the panic of a worker that panicked.
+    fn receive(&mut self) -> Option<Message> {
+        if let Ok(message) = self.receiver.recv() {
+            return Some(message);
+        }
+
+        // the channel disconnects once all workers are done
+        for worker in self.workers.drain(..) {
+            if let Err(payload) = worker.join() {
+                panic::resume_unwind(payload);
+            }
+        }
+
+        None
+    }
+}
+
+/// The bookkeeping for returning solutions in the order of their tasks.
+#[derive(Debug)]
+struct Ordered {
+    /// The task whose solutions are returned next.
+    current: usize,
+    num_tasks: usize,
+    /// The solutions received for tasks after `current`, and whether the
+    /// task is done.
+    pending: HashMap<usize, (VecDeque<Sudoku>, bool)>,
+}
+
+impl Iterator for ParallelSolutions {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Sudoku> {
+        if self.ordered.is_none() {
+            while let Some((_, solution)) = self.receive() {
+                if solution.is_some() {
+                    return solution;
+                }
+            }
+
+            return None;
+        }
+
+        loop {
+            let ordered = self.ordered.as_mut().expect("solutions are ordered");
+            if ordered.current >= ordered.num_tasks {
+                return None;
+            }
+
+            let (solutions, done) = ordered.pending.entry(ordered.current).or_default();
+
+            if let Some(solution) = solutions.pop_front() {
+                return Some(solution);
+            }
+
+            if *done {
+                ordered.pending.remove(&ordered.current);
+                ordered.current += 1;
+                continue;
+            }
+
+            // workers finish every task they start, unless they panic
+            let (index, solution) = self.receive()?;
+            let ordered = self.ordered.as_mut().expect("solutions are ordered");
+            let (solutions, done) = ordered.pending.entry(index).or_default();
+            match solution {
+                Some(solution) => solutions.push_back(solution),
+                None => *done = true,
+            }
+        }
+    }
+}
+
+/// Split the search for the solutions of `sudoku` into at least `target`
+/// sub-puzzles (unless the search tree is too small), level by level.
+///
+/// Unsolvable sub-puzzles are left out as soon as they are found.
+///
+/// Every solution of `sudoku` is the solution of exactly one sub-puzzle.
+fn split(sudoku: &Sudoku, target: usize) -> Vec<Sudoku> {
+    if !sudoku.is_valid() {
+        return Vec::new();
+    }
+
+    let mut tasks = vec![*sudoku];
+
+    // Every level fills in one more square of every task, so this ends after
+    // at most 81 levels.
+    while tasks.len() < target {
+        let mut split_any = false;
+        let mut next_tasks = Vec::with_capacity(tasks.len() * 2);
+
+        for task in tasks {
+            match Branching::FewestOptions.choose(&candidates(&task), |square| task.grid[square]) {
+                Some(branch) => {
+                    split_any = true;
+                    next_tasks.extend(children(&task, branch));
+                },
+                // already solved
+                None => next_tasks.push(task),
+            }
+        }
+
+        tasks = next_tasks;
+        if !split_any {
+            break;
+        }
+    }
+
+    tasks
+}
+
+/// Get a copy of `sudoku` for every alternative of `branch`.
+fn children(sudoku: &Sudoku, branch: Branch) -> Vec<Sudoku> {
+    let placements = match branch {
+        Branch::Square { square, values } => (0..9)
+            .filter(|value| values >> value & 1 == 1)
+            .map(|value| (square, value + 1))
+            .collect::<Vec<_>>(),
+        Branch::House { house, value, squares } => house
+            .squares()
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, _)| squares >> i & 1 == 1)
+            .map(|(_, (x, y))| (x + y * 9, value))
+            .collect(),
+    };
+
+    placements
+        .into_iter()
+        .map(|(square, value)| {
+            let mut child = *sudoku;
+            child.grid[square] = value;
+            child
+        })
+        .collect()
+}
+
+/// Get the candidates of every square of `sudoku` as bitflags, bit
+/// `value - 1` for `value`.
+fn candidates(sudoku: &Sudoku) -> [u16; NUM_SQUARES] {
+    let mut rows = [0u16; 9];
+    let mut columns = [0u16; 9];
+    let mut boxes = [0u16; 9];
+
+    for (square, &value) in sudoku.grid.iter().enumerate() {
+        if value != 0 {
+            let (x, y) = (square % 9, square / 9);
+            rows[y] |= 1 << (value - 1);
+            columns[x] |= 1 << (value - 1);
+            boxes[x / 3 + (y / 3) * 3] |= 1 << (value - 1);
+        }
+    }
+
+    std::array::from_fn(|square| {
+        let (x, y) = (square % 9, square / 9);
+        !(rows[y] | columns[x] | boxes[x / 3 + (y / 3) * 3]) & 0x1ff
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use itertools::Itertools;
+
+    use crate::Sudoku;
+    use crate::solver::{Branching, Engine, Solver};
+
+    use super::{split, ParallelSolver};
+
+    /// A puzzle with 3 solutions
+    ///
+    /// taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+    const THREE_SOLUTIONS: &str = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4";
+
+    /// A puzzle with 1356 solutions: the first 5 rows of a solved grid.
+    fn many_solutions() -> Sudoku {
+        let solved = "726853419159274863483619275618925734942367158375481692294736581537148926861592347";
+
+        (solved[..45].to_owned() + &".".repeat(36)).parse().unwrap()
+    }
+
+    #[test]
+    fn count_matches_sequential() {
+        let sudoku = many_solutions();
+        let expected = sudoku.find_all_solutions().count();
+
+        for threads in [1, 3, 8] {
+            assert_eq!(ParallelSolver::new().threads(threads).count_solutions(&sudoku), expected);
+        }
+
+        let three: Sudoku = THREE_SOLUTIONS.parse().unwrap();
+        assert_eq!(ParallelSolver::new().count_solutions(&three), 3);
+    }
+
+    #[test]
+    fn find_all_matches_sequential() {
+        let sudoku = many_solutions();
+        let expected = sudoku.find_all_solutions().sorted_by_key(|solution| solution.grid).collect::<Vec<_>>();
+
+        for ordered in [false, true] {
+            let solver = ParallelSolver::new()
+                .threads(4)
+                .ordered(ordered)
+                .solver(Solver::new().engine(Engine::DancingLinks));
+
+            let found = solver.find_all_solutions(&sudoku).sorted_by_key(|solution| solution.grid).collect::<Vec<_>>();
+
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn ordered_is_deterministic() {
+        let sudoku = many_solutions();
+
+        let solutions = [1, 2, 5].map(|threads| {
+            ParallelSolver::new()
+                .threads(threads)
+                .ordered(true)
+                .find_all_solutions(&sudoku)
+                .collect::<Vec<_>>()
+        });
+
+        assert!(solutions.iter().all_equal());
+
+        for branching in Branching::ALL {
+            let solutions = [1, 8].map(|threads| {
+                ParallelSolver::new()
+                    .threads(threads)
+                    .ordered(true)
+                    .solver(Solver::new().branching(branching))
+                    .find_all_solutions(&sudoku)
+                    .collect::<Vec<_>>()
+            });
+
+            assert!(solutions.iter().all_equal(), "{:?}", branching);
+        }
+    }
+
+    #[test]
+    fn unsolvable_and_solved() {
+        let invalid: Sudoku = ("11".to_owned() + &".".repeat(79)).parse().unwrap();
+        assert_eq!(ParallelSolver::new().count_solutions(&invalid), 0);
+        assert_eq!(ParallelSolver::new().find_all_solutions(&invalid).next(), None);
+
+        let dead_end: Sudoku = ("12.456789..3".to_owned() + &".".repeat(69)).parse().unwrap();
+        assert_eq!(ParallelSolver::new().count_solutions(&dead_end), 0);
+
+        let solved: Sudoku = "726853419159274863483619275618925734942367158375481692294736581537148926861592347".parse().unwrap();
+        assert_eq!(ParallelSolver::new().ordered(true).find_all_solutions(&solved).collect::<Vec<_>>(), vec![solved]);
+    }
+
+    #[test]
+    fn split_covers_every_solution_once() {
+        let sudoku = many_solutions();
+
+        let tasks = split(&sudoku, 32);
+        assert!(tasks.len() >= 32);
+
+        let count: usize = tasks.iter().map(|task| task.find_all_solutions().count()).sum();
+        assert_eq!(count, sudoku.find_all_solutions().count());
+    }
+}
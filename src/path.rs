@@ -0,0 +1,260 @@
+//! The whole chain of deductions the [LogicalSolver] makes to solve a puzzle.
+//!
+//! A [SolutionPath] lists every [SolveStep] in order, together with the grid
+//! and the candidates after the step. It can be exported as JSON with
+//! [SolutionPath::to_json()] or as a text log for humans with
+//! [SolutionPath::to_text()].
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::logic::Technique;
+//!
+//! // The solution of a puzzle generated with http://www.opensky.ca/sudoku,
+//! // with only the first and the last square left empty
+//! let sudoku: Sudoku = ".3751986219286374565872431956139842798427615372345169821963758487694523134518297.".parse().unwrap();
+//!
+//! let path = sudoku.solution_path();
+//! assert!(path.is_solved());
+//! assert_eq!(path.steps.len(), 2);
+//!
+//! let first = &path.steps[0];
+//! assert_eq!(first.step.technique, Technique::HiddenSingle);
+//! assert_eq!(first.grid.get_value(0, 0), 4);
+//! assert!(first.candidates[0].is_empty());
+//! assert_eq!(first.candidates[80].to_string(), "6");
+//!
+//! assert!(path.to_text().ends_with("\nSolved in 2 steps.\n"));
+//! ```
+//!
+//! [LogicalSolver]: crate::logic::LogicalSolver
+
+use crate::{Sudoku, NUM_SQUARES};
+use crate::candidates::Candidates;
+use crate::explain::{English, StepFormatter};
+use crate::logic::{LogicalSolver, SolveStep};
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt::Write;
+
+/// The steps of the [LogicalSolver] from a puzzle up to its solution, or up
+/// to where the solver got stuck.
+///
+/// Candidates are stored for all 81 squares in the order of
+/// [Sudoku::new_from_array()]. Squares that contain a value have no
+/// candidates.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SolutionPath {
+    /// The puzzle the path starts from.
+    pub puzzle: Sudoku,
+    /// The candidates of the puzzle, see [Sudoku::candidates()].
+    #[serde(serialize_with = "crate::serialize_squares", deserialize_with = "crate::deserialize_squares")]
+    pub candidates: [Candidates; NUM_SQUARES],
+    /// The steps in the order in which they were applied.
+    pub steps: Vec<PathStep>,
+}
+
+/// A [SolveStep] of a [SolutionPath] and the state after applying it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PathStep {
+    /// The step that was applied.
+    pub step: SolveStep,
+    /// The grid after the step.
+    pub grid: Sudoku,
+    /// The candidates after the step.
+    #[serde(serialize_with = "crate::serialize_squares", deserialize_with = "crate::deserialize_squares")]
+    pub candidates: [Candidates; NUM_SQUARES],
+}
+
+impl SolutionPath {
+
+    /// Run the [LogicalSolver] on `puzzle` until it is solved or the solver
+    /// gets stuck, and record every step.
+    pub fn new(puzzle: &Sudoku) -> SolutionPath {
+        let mut solver = LogicalSolver::new(puzzle);
+        let candidates = solver.candidates();
+
+        let mut steps = Vec::new();
+        while let Some(step) = solver.step() {
+            steps.push(PathStep {
+                step,
+                grid: *solver.sudoku(),
+                candidates: solver.candidates(),
+            });
+        }
+
+        SolutionPath {
+            puzzle: *puzzle,
+            candidates,
+            steps,
+        }
+    }
+
+    /// Get the grid at the end of the path.
+    pub fn grid(&self) -> &Sudoku {
+        self.steps.last().map_or(&self.puzzle, |step| &step.grid)
+    }
+
+    /// Check if the path ends with a solved grid. Otherwise, the
+    /// [LogicalSolver] got stuck.
+    pub fn is_solved(&self) -> bool {
+        self.grid().is_solved()
+    }
+
+    /// Serialize this path as JSON.
+    ///
+    /// Grids are serialized as strings of 81 digits (see [Sudoku]'s
+    /// [Serialize] implementation) and candidates as lists of values.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a SolutionPath can always be serialized")
+    }
+
+    /// Write this path as a text log for humans, explaining the steps in
+    /// English.
+    ///
+    /// See [SolutionPath::to_text_with()].
+    pub fn to_text(&self) -> String {
+        self.to_text_with(&English)
+    }
+
+    /// Write this path as a text log for humans, explaining the steps with
+    /// `formatter`.
+    ///
+    /// The log starts with the puzzle and its candidates. Every step is
+    /// numbered and followed by the grid and the candidates after it, where
+    /// squares containing a value are shown as `.`. The last line tells
+    /// whether the puzzle was solved.
+    pub fn to_text_with(&self, formatter: &impl StepFormatter) -> String {
+        let mut text = String::new();
+
+        // Writing to a String cannot fail
+        let _ = writeln!(text, "Puzzle: {}", self.puzzle.line_repr());
+        write_candidates(&mut text, &self.candidates);
+
+        for (number, step) in (1..).zip(&self.steps) {
+            let _ = writeln!(text, "\nStep {}: {}", number, formatter.format_step(&step.step));
+            let _ = writeln!(text, "Grid: {}", step.grid.line_repr());
+            write_candidates(&mut text, &step.candidates);
+        }
+
+        if self.is_solved() {
+            let _ = writeln!(text, "\nSolved in {} steps.", self.steps.len());
+        } else {
+            let _ = writeln!(text, "\nStuck after {} steps.", self.steps.len());
+        }
+
+        text
+    }
+}
+
+/// Write the candidates of all squares as a grid, with the columns aligned
+/// and lines between the boxes.
+fn write_candidates(text: &mut String, candidates: &[Candidates; NUM_SQUARES]) {
+    let cells = candidates
+        .iter()
+        .map(|candidates| if candidates.is_empty() { ".".to_owned() } else { candidates.to_string() })
+        .collect::<Vec<_>>();
+    let widths = (0..9)
+        .map(|x| (0..9).map(|y| cells[x + y * 9].len()).max().unwrap_or(1))
+        .collect::<Vec<_>>();
+
+    for y in 0..9 {
+        if y == 3 || y == 6 {
+            let [left, middle, right] = [0..3, 3..6, 6..9].map(|columns| widths[columns].iter().sum::<usize>() + 2);
+            let _ = writeln!(text, "{}+{}+{}", "-".repeat(left + 1), "-".repeat(middle + 2), "-".repeat(right + 1));
+        }
+
+        let row = (0..9)
+            .map(|x| format!("{:<width$}", cells[x + y * 9], width = widths[x]))
+            .collect::<Vec<_>>();
+        let line = format!("{} | {} | {}", row[0..3].join(" "), row[3..6].join(" "), row[6..9].join(" "));
+        let _ = writeln!(text, "{}", line.trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::Sudoku;
+    use crate::explain::StepFormatter;
+    use crate::logic::{LogicalSolver, SolveStep};
+    use crate::tests::PUZZLE;
+
+    use super::SolutionPath;
+
+    #[test]
+    fn follows_solver() {
+        let puzzle: Sudoku = PUZZLE.parse().unwrap();
+        let path = SolutionPath::new(&puzzle);
+
+        assert!(path.is_solved());
+        assert_eq!(*path.grid(), puzzle.find_solution().unwrap());
+        assert_eq!(path.candidates, puzzle.candidates());
+
+        let steps = LogicalSolver::new(&puzzle).collect::<Vec<_>>();
+        assert_eq!(path.steps.iter().map(|step| step.step.clone()).collect::<Vec<_>>(), steps);
+
+        for step in &path.steps {
+            for placement in &step.step.placements {
+                assert_eq!(step.grid.get_value(placement.x, placement.y), placement.value);
+            }
+            for elimination in &step.step.eliminations {
+                assert!(!step.candidates[elimination.x + elimination.y * 9].contains(elimination.value));
+            }
+        }
+    }
+
+    #[test]
+    fn stuck() {
+        let puzzle = Sudoku::new_empty();
+        let path = puzzle.solution_path();
+
+        assert!(!path.is_solved());
+        assert!(path.steps.is_empty());
+        assert_eq!(*path.grid(), puzzle);
+        assert!(path.to_text().ends_with("\nStuck after 0 steps.\n"));
+    }
+
+    #[test]
+    fn json() {
+        let path = PUZZLE.parse::<Sudoku>().unwrap().solution_path();
+        let json = path.to_json();
+
+        assert!(json.starts_with("{\"puzzle\":\"001020900"));
+        assert!(json.contains("\"candidates\":[[3,5,6,7,8],[4,5,6,8],[],"));
+        assert_eq!(serde_json::from_str::<SolutionPath>(&json).unwrap(), path);
+
+        let puzzle = Sudoku::new_empty().line_repr();
+        assert!(serde_json::from_str::<SolutionPath>(&format!("{{\"puzzle\":\"{}\",\"candidates\":[],\"steps\":[]}}", puzzle)).is_err());
+    }
+
+    #[test]
+    fn text() {
+        let path = PUZZLE.parse::<Sudoku>().unwrap().solution_path();
+        let text = path.to_text();
+        let lines = text.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], format!("Puzzle: {}", path.puzzle.line_repr()));
+        assert!(lines[1].starts_with("35678 4568 . "));
+        assert!(lines[4].starts_with("------"));
+        assert_eq!(lines[1].find('|'), lines[4].find('+'));
+        assert_eq!(lines[13], "Step 1: Hidden single: 6 must go in r4c1 because it is the only place in box 4.");
+        assert!(lines[14].starts_with("Grid: "));
+        assert_eq!(*lines.last().unwrap(), format!("Solved in {} steps.", path.steps.len()));
+    }
+
+    struct Techniques;
+
+    impl StepFormatter for Techniques {
+        fn format_step(&self, step: &SolveStep) -> String {
+            step.technique.to_string()
+        }
+    }
+
+    #[test]
+    fn text_with_formatter() {
+        let path = PUZZLE.parse::<Sudoku>().unwrap().solution_path();
+
+        assert!(path.to_text_with(&Techniques).contains("\nStep 1: Hidden Single\n"));
+    }
+}
@@ -0,0 +1,346 @@
+//! Pencil marks a player makes while solving a [Sudoku].
+//!
+//! Contrary to the [Candidates] computed by [Sudoku::candidates()], which
+//! always follow from the values on the grid, [PencilMarks] only change when
+//! they are edited. This way, candidates a player eliminated by reasoning stay
+//! eliminated. [PencilMarks::check()] compares the pencil marks to the
+//! solution to find candidates that were eliminated by mistake.
+//!
+//! A square that was never pencil marked is told apart from one whose marks
+//! were all removed, see [PencilMarks::is_marked()].
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::pencil_marks::{PencilMarkMistake, PencilMarks};
+//!
+//! // Values generated with http://www.opensky.ca/sudoku
+//! let puzzle: Sudoku = "....7581...24..7......3..4.2....7.53.1.2.9.8.59.3....4.2..6......9..43...4871....".parse().unwrap();
+//! let solution = puzzle.find_solution().unwrap();
+//!
+//! let mut pencil_marks = PencilMarks::from_candidates(&puzzle);
+//! assert_eq!(pencil_marks.get(1, 0).to_string(), "36");
+//!
+//! pencil_marks.remove(1, 0, 6);
+//! assert!(pencil_marks.check(&puzzle, &solution).is_empty());
+//!
+//! // 3 is the solution of (1 / 0)
+//! pencil_marks.remove(1, 0, 3);
+//! assert_eq!(pencil_marks.check(&puzzle, &solution),
+//!            vec![PencilMarkMistake::WronglyRemoved { x: 1, y: 0, value: 3 }]);
+//! ```
+//!
+//! [Sudoku]: crate::Sudoku
+//! [Sudoku::candidates()]: crate::Sudoku::candidates()
+
+use crate::{Sudoku, SudokuError, NUM_SQUARES};
+use crate::candidates::Candidates;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The pencil marks of every square of a [Sudoku] grid.
+///
+/// Pencil marks can be made in any square, whether it holds a value or not,
+/// but they are only meaningful while the square is empty.
+///
+/// [PencilMarks] are serialized as a sequence of the [Candidates] of all 81
+/// squares in the order of [Sudoku::new_from_array()], with `null` for
+/// squares that were never pencil marked.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PencilMarks {
+    /// The marks of every square, `None` if it was never pencil marked.
+    marks: [Option<Candidates>; NUM_SQUARES],
+}
+
+impl PencilMarks {
+
+    /// Initialize new [PencilMarks] without any marks.
+    pub fn new() -> PencilMarks {
+        PencilMarks {
+            marks: [None; NUM_SQUARES],
+        }
+    }
+
+    /// Initialize new [PencilMarks] marking all [Candidates] of every empty
+    /// square of `sudoku`, see [Sudoku::candidates()].
+    pub fn from_candidates(sudoku: &Sudoku) -> PencilMarks {
+        let candidates = sudoku.candidates();
+
+        PencilMarks {
+            marks: std::array::from_fn(|i| (sudoku.grid[i] == 0).then_some(candidates[i])),
+        }
+    }
+
+    /// Get the values marked in the square at (`x` / `y`).
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Candidates {
+        self.marks[square(x, y)].unwrap_or_default()
+    }
+
+    /// Replace the marks of the square at (`x` / `y`) with `marks`.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, marks: Candidates) {
+        self.marks[square(x, y)] = Some(marks);
+    }
+
+    /// Check if the square at (`x` / `y`) was pencil marked, that is if it
+    /// was edited or filled in by [PencilMarks::from_candidates()].
+    ///
+    /// A pencil marked square stays marked when all of its marks are
+    /// removed, see [PencilMarks::check()].
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn is_marked(&self, x: usize, y: usize) -> bool {
+        self.marks[square(x, y)].is_some()
+    }
+
+    /// Forget the marks of the square at (`x` / `y`), as if it was never
+    /// pencil marked.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn unmark(&mut self, x: usize, y: usize) {
+        self.marks[square(x, y)] = None;
+    }
+
+    /// Check if `value` is marked in the square at (`x` / `y`).
+    ///
+    /// Panics if the coordinates or the value are out of bounds.
+    pub fn contains(&self, x: usize, y: usize, value: u32) -> bool {
+        self.get(x, y).contains(value)
+    }
+
+    /// Mark `value` in the square at (`x` / `y`).
+    ///
+    /// Return `true` if `value` was not marked before.
+    ///
+    /// Panics if the coordinates or the value are out of bounds.
+    pub fn add(&mut self, x: usize, y: usize, value: u32) -> bool {
+        self.marks[square(x, y)].get_or_insert_with(Candidates::new).insert(value)
+    }
+
+    /// Remove the mark for `value` from the square at (`x` / `y`).
+    ///
+    /// Return `true` if `value` was marked before.
+    ///
+    /// Panics if the coordinates or the value are out of bounds.
+    pub fn remove(&mut self, x: usize, y: usize, value: u32) -> bool {
+        self.marks[square(x, y)].get_or_insert_with(Candidates::new).remove(value)
+    }
+
+    /// Mark `value` in the square at (`x` / `y`) if it is not marked yet,
+    /// remove the mark otherwise.
+    ///
+    /// Return `true` if `value` is marked afterwards.
+    ///
+    /// Panics if the coordinates or the value are out of bounds.
+    pub fn toggle(&mut self, x: usize, y: usize, value: u32) -> bool {
+        self.marks[square(x, y)].get_or_insert_with(Candidates::new).toggle(value)
+    }
+
+    /// Remove all marks from the square at (`x` / `y`). The square stays
+    /// pencil marked, see [PencilMarks::unmark()] to forget it instead.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn clear(&mut self, x: usize, y: usize) {
+        self.set(x, y, Candidates::new());
+    }
+
+    /// Check if no value is marked in any square.
+    pub fn is_empty(&self) -> bool {
+        self.marks.iter().flatten().all(Candidates::is_empty)
+    }
+
+    /// Compare the marks of every empty square of `grid` to `solution`.
+    ///
+    /// A pencil marked square without the mark for its solution is reported
+    /// as [PencilMarkMistake::WronglyRemoved], even if all of its marks were
+    /// removed. Squares that were never pencil marked are skipped (see
+    /// [PencilMarks::is_marked()]). A marked value that already appears in
+    /// the row, the column or the 3x3 cell of the square is reported as
+    /// [PencilMarkMistake::Impossible].
+    ///
+    /// The mistakes are returned going row by row.
+    pub fn check(&self, grid: &Sudoku, solution: &Sudoku) -> Vec<PencilMarkMistake> {
+        let candidates = grid.candidates();
+        let mut mistakes = Vec::new();
+
+        for y in 0..9 {
+            for x in 0..9 {
+                let marks = match self.marks[x + y * 9] {
+                    Some(marks) if grid.get_value(x, y) == 0 => marks,
+                    _ => continue,
+                };
+
+                let value = solution.get_value(x, y);
+                if value != 0 && !marks.contains(value) {
+                    mistakes.push(PencilMarkMistake::WronglyRemoved { x, y, value });
+                }
+
+                mistakes.extend(
+                    (marks - candidates[x + y * 9])
+                        .iter()
+                        .map(|value| PencilMarkMistake::Impossible { x, y, value })
+                );
+            }
+        }
+
+        mistakes
+    }
+}
+
+impl Default for PencilMarks {
+    fn default() -> PencilMarks {
+        PencilMarks::new()
+    }
+}
+
+/// Get the index of the square at (`x` / `y`).
+///
+/// Panics if the coordinates are out of bounds.
+fn square(x: usize, y: usize) -> usize {
+    if x > 8 || y > 8 {
+        panic!("{}", SudokuError::InvalidCoordinates { x, y });
+    }
+
+    x + y * 9
+}
+
+impl Serialize for PencilMarks {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serialize_squares(&self.marks, serializer)
+    }
+}
+
+/// [PencilMarks] are deserialized from a sequence of exactly 81
+/// [Candidates] or `null`s.
+impl<'de> Deserialize<'de> for PencilMarks {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<PencilMarks, D::Error> {
+        let marks = crate::deserialize_squares(deserializer)?;
+
+        Ok(PencilMarks { marks })
+    }
+}
+
+/// A pencil mark that contradicts the solution, see [PencilMarks::check()].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum PencilMarkMistake {
+    /// The solution of the square at (`x` / `y`) is not marked.
+    WronglyRemoved {
+        /// The x coordinate of the square.
+        x: usize,
+        /// The y coordinate of the square.
+        y: usize,
+        /// The solution of the square.
+        value: u32,
+    },
+    /// `value` is marked in the square at (`x` / `y`), but it already
+    /// appears in the row, the column or the 3x3 cell of the square.
+    Impossible {
+        /// The x coordinate of the square.
+        x: usize,
+        /// The y coordinate of the square.
+        y: usize,
+        /// The marked value.
+        value: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::Sudoku;
+    use crate::candidates::Candidates;
+    use crate::tests::PUZZLE;
+
+    use super::{PencilMarkMistake, PencilMarks};
+
+    #[test]
+    fn edit_marks() {
+        let mut pencil_marks = PencilMarks::new();
+        assert!(pencil_marks.is_empty());
+
+        assert!(pencil_marks.add(4, 7, 2));
+        assert!(!pencil_marks.add(4, 7, 2));
+        assert!(pencil_marks.toggle(4, 7, 6));
+        assert_eq!(pencil_marks.get(4, 7), Candidates::from_iter([2, 6]));
+
+        assert!(!pencil_marks.toggle(4, 7, 2));
+        assert!(pencil_marks.remove(4, 7, 6));
+        assert!(!pencil_marks.contains(4, 7, 6));
+        assert!(pencil_marks.is_empty());
+
+        pencil_marks.set(0, 8, Candidates::all());
+        pencil_marks.clear(0, 8);
+        assert!(pencil_marks.is_marked(0, 8));
+        assert!(pencil_marks.is_empty());
+
+        pencil_marks.unmark(0, 8);
+        pencil_marks.unmark(4, 7);
+        assert_eq!(pencil_marks, PencilMarks::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_panics_on_invalid_coordinates() {
+        PencilMarks::new().get(0, 9);
+    }
+
+    #[test]
+    fn marks_persist() {
+        let mut puzzle: Sudoku = PUZZLE.parse().unwrap();
+        let mut pencil_marks = PencilMarks::from_candidates(&puzzle);
+        pencil_marks.remove(0, 0, 7);
+
+        puzzle.set_value(0, 0, 5);
+        assert!(pencil_marks.contains(0, 0, 3));
+        assert!(!pencil_marks.contains(0, 0, 7));
+        assert!(pencil_marks.contains(1, 0, 5));
+    }
+
+    #[test]
+    fn check() {
+        let grid: Sudoku = PUZZLE.parse().unwrap();
+        let solution = grid.find_solution().unwrap();
+
+        let mut pencil_marks = PencilMarks::from_candidates(&grid);
+        assert!(pencil_marks.check(&grid, &solution).is_empty());
+
+        // 1 is given in (2 / 0) and 3 is the solution of (3 / 0)
+        pencil_marks.add(0, 0, 1);
+        pencil_marks.remove(3, 0, 3);
+        pencil_marks.remove(3, 0, 6);
+        pencil_marks.clear(5, 0);
+        pencil_marks.clear(7, 0);
+        pencil_marks.add(7, 0, 9);
+
+        assert_eq!(pencil_marks.check(&grid, &solution), vec![
+            PencilMarkMistake::Impossible { x: 0, y: 0, value: 1 },
+            PencilMarkMistake::WronglyRemoved { x: 3, y: 0, value: 3 },
+            PencilMarkMistake::WronglyRemoved { x: 5, y: 0, value: 7 },
+            PencilMarkMistake::WronglyRemoved { x: 7, y: 0, value: 8 },
+            PencilMarkMistake::Impossible { x: 7, y: 0, value: 9 },
+        ]);
+
+        // squares that were never pencil marked are skipped
+        pencil_marks.unmark(5, 0);
+        pencil_marks.unmark(7, 0);
+        assert_eq!(pencil_marks.check(&grid, &solution).len(), 2);
+        assert!(PencilMarks::new().check(&grid, &solution).is_empty());
+    }
+
+    #[test]
+    fn serde() {
+        let puzzle: Sudoku = PUZZLE.parse().unwrap();
+        let mut pencil_marks = PencilMarks::from_candidates(&puzzle);
+        pencil_marks.remove(0, 0, 7);
+
+        pencil_marks.unmark(1, 0);
+
+        let json = serde_json::to_string(&pencil_marks).unwrap();
+        assert!(json.starts_with("[[3,5,6,8],null,null,"));
+        assert_eq!(serde_json::from_str::<PencilMarks>(&json).unwrap(), pencil_marks);
+
+        assert!(serde_json::from_str::<PencilMarks>("[[1],[2]]").is_err());
+    }
+}
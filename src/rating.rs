@@ -0,0 +1,189 @@
+//! Rating the difficulty of [Sudoku] puzzles.
+//!
+//! A puzzle is rated by solving it with the [LogicalSolver] and looking at
+//! the hardest [Technique] that was needed. See [Sudoku::rate()].
+
+use crate::Sudoku;
+use crate::logic::{LogicalSolver, SolveStep, Technique};
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// A rough difficulty label for a [Sudoku] puzzle.
+///
+/// The labels are ordered from easiest to hardest, so they can be compared
+/// with each other. They are serialized in snake case, e.g.
+/// `"requires_guessing"`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    /// Can be solved with singles only.
+    Easy,
+    /// Requires locked candidates or naked pairs.
+    Medium,
+    /// Requires hidden pairs, triples or basic fish (X-Wing, Swordfish).
+    Hard,
+    /// Requires wings, quads or a Jellyfish.
+    Expert,
+    /// Cannot be solved by the [LogicalSolver] without guessing.
+    RequiresGuessing,
+}
+
+impl Difficulty {
+
+    /// Get the [Difficulty] of a puzzle that can be solved logically and
+    /// whose hardest step has the given rating.
+    ///
+    /// ```
+    /// use sudoku::logic::Technique;
+    /// use sudoku::rating::Difficulty;
+    ///
+    /// assert_eq!(Difficulty::from_score(Technique::NakedSingle.rating()), Difficulty::Easy);
+    /// assert_eq!(Difficulty::from_score(Technique::XWing.rating()), Difficulty::Hard);
+    /// ```
+    pub fn from_score(score: f32) -> Difficulty {
+        if score < 2.5 {
+            Difficulty::Easy
+        } else if score < 3.2 {
+            Difficulty::Medium
+        } else if score < 4.2 {
+            Difficulty::Hard
+        } else {
+            Difficulty::Expert
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+            Difficulty::RequiresGuessing => "requires guessing",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// The result of rating a [Sudoku] puzzle, see [Sudoku::rate()].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    /// The difficulty label of the puzzle.
+    pub difficulty: Difficulty,
+    /// The rating of the hardest step that was needed, on a scale similar to
+    /// the one of Sudoku Explainer (see [Technique::rating()]).
+    ///
+    /// If the puzzle requires guessing, this only covers the steps that could
+    /// be made before the [LogicalSolver] got stuck. It is 0 if no step was
+    /// needed at all.
+    pub score: f32,
+    /// The hardest technique that was needed, if any.
+    pub hardest_technique: Option<Technique>,
+    /// The number of steps taken by the [LogicalSolver].
+    pub num_steps: usize,
+}
+
+/// Rate a [Sudoku] puzzle. See [Sudoku::rate()].
+pub(crate) fn rate(sudoku: &Sudoku) -> Option<Rating> {
+    let mut solver = LogicalSolver::new(sudoku);
+    let mut score = 0.0;
+    let mut hardest_technique = None;
+    let mut num_steps = 0;
+
+    for step in solver.by_ref() {
+        let step_score = step_rating(&step);
+        if step_score > score {
+            score = step_score;
+            hardest_technique = Some(step.technique);
+        }
+        num_steps += 1;
+    }
+
+    let difficulty = if solver.sudoku().is_solved() {
+        Difficulty::from_score(score)
+    } else if sudoku.is_solvable() {
+        Difficulty::RequiresGuessing
+    } else {
+        return None;
+    };
+
+    Some(Rating {
+        difficulty,
+        score,
+        hardest_technique,
+        num_steps,
+    })
+}
+
+/// Get the rating of a single step.
+///
+/// Hidden singles within a box are easier to spot than the ones within a row
+/// or column, so they are rated lower.
+fn step_rating(step: &SolveStep) -> f32 {
+    match (step.technique, step.houses.first()) {
+        (Technique::HiddenSingle, Some(crate::House::Box(_))) => 1.2,
+        (technique, _) => technique.rating(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::Sudoku;
+    use crate::logic::Technique;
+
+    use super::Difficulty;
+
+    #[test]
+    fn rate_easy_puzzle() {
+        // Values generated with http://www.opensky.ca/sudoku
+        let sudoku: Sudoku = "43...98..19.8....5...724....61.9..2...........2..5.69....637...8....5.31..51...76".parse().unwrap();
+
+        let rating = sudoku.rate().unwrap();
+
+        assert_eq!(rating.difficulty, Difficulty::Easy);
+        assert_eq!(rating.num_steps, sudoku.num_empty_squares());
+    }
+
+    #[test]
+    fn rate_puzzle_requiring_guessing() {
+        // taken from https://puzzling.stackexchange.com/questions/67789/examples-of-sudokus-with-two-solutions
+        let sudoku: Sudoku = "2957438614318659..8761925433874592166123874955492167387635241899286713541549386..".parse().unwrap();
+
+        let rating = sudoku.rate().unwrap();
+
+        assert_eq!(rating.difficulty, Difficulty::RequiresGuessing);
+        assert_eq!(rating.hardest_technique, None);
+        assert_eq!(rating.num_steps, 0);
+    }
+
+    #[test]
+    fn rate_solved_sudoku() {
+        let solved: Sudoku = "726853419159274863483619275618925734942367158375481692294736581537148926861592347".parse().unwrap();
+
+        let rating = solved.rate().unwrap();
+
+        assert_eq!(rating.difficulty, Difficulty::Easy);
+        assert_eq!(rating.score, 0.0);
+    }
+
+    #[test]
+    fn rate_unsolvable_sudoku() {
+        let unsolvable: Sudoku = ("12.456789..3".to_owned() + &".".repeat(69)).parse().unwrap();
+
+        assert_eq!(unsolvable.rate(), None);
+    }
+
+    #[test]
+    fn from_score_follows_technique_order() {
+        let difficulties = Technique::ALL.map(|technique| Difficulty::from_score(technique.rating()));
+
+        assert!(difficulties.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(difficulties.first(), Some(&Difficulty::Easy));
+        assert_eq!(difficulties.last(), Some(&Difficulty::Expert));
+    }
+}
@@ -0,0 +1,623 @@
+//! Choosing the engine used to solve [Sudoku] puzzles.
+//!
+//! The methods of [Sudoku] like [Sudoku::find_solution()] always use the
+//! default [Engine]. A [Solver] offers the same methods, but lets you pick the
+//! engine, e.g. to use the fastest one for bulk work.
+//!
+//! ```
+//! use sudoku::Sudoku;
+//! use sudoku::solver::{Engine, Solver};
+//!
+//! // Values generated with http://www.opensky.ca/sudoku. This puzzle cannot
+//! // be solved without guessing.
+//! let sudoku: Sudoku = ".6.......9..3684..7...1.9..1....95.8.36...79.8.97....2..4.9...5..1256..9.......1.".parse().unwrap();
+//!
+//! let solver = Solver::new().engine(Engine::DancingLinks);
+//!
+//! assert_eq!(solver.find_solution(&sudoku), sudoku.find_solution());
+//! ```
+
+use crate::{AllSolutionsIterator, BacktrackingState, House, SolutionCount, Sudoku, NUM_SQUARES};
+use crate::bitboard::{Bitboard, BitboardState};
+use crate::dlx::{DancingLinks, DancingLinksState};
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// The algorithm used by a [Solver].
+///
+/// All engines find the same solutions, but not necessarily in the same order.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Engine {
+    /// Keep a bitmask of the values used within every row, column and box,
+    /// update the masks with every placement and always guess on the square
+    /// with the fewest candidates. Usually the fastest engine.
+    #[default]
+    Bitboard,
+    /// Fill in values that are the only possibility for their square and
+    /// guess whenever that gets stuck. Needs very little memory, but is the
+    /// slowest engine.
+    Backtracking,
+    /// Solve the puzzle as an exact cover problem using Donald Knuth's
+    /// Dancing Links.
+    DancingLinks,
+}
+
+impl Engine {
+
+    /// All engines.
+    pub const ALL: [Engine; 3] = [Engine::Bitboard, Engine::Backtracking, Engine::DancingLinks];
+}
+
+/// How the [Engine::Bitboard] and [Engine::Backtracking] engines decide where
+/// to guess when they get stuck.
+///
+/// [Engine::DancingLinks] always branches on the constraint with the fewest
+/// options, which is the same as [Branching::FewestOptions].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Branching {
+    /// Try every candidate of the first empty square, going row by row.
+    FirstEmpty,
+    /// Try every candidate of the empty square with the fewest candidates.
+    FewestCandidates,
+    /// Like [Branching::FewestCandidates], but if some value has fewer
+    /// possible squares within a row, column or box than the best square has
+    /// candidates, try every one of those squares instead. This finds hidden
+    /// singles as well as naked singles without any guessing.
+    #[default]
+    FewestOptions,
+}
+
+impl Branching {
+
+    /// All branching strategies.
+    pub const ALL: [Branching; 3] = [Branching::FirstEmpty, Branching::FewestCandidates, Branching::FewestOptions];
+
+    /// Choose where to branch.
+    ///
+    /// `value` returns the value on a square, 0 if it is empty. `candidates`
+    /// contains a bit for every value that is still possible on a square, bit
+    /// `value - 1` for `value`. It is only looked at for empty squares.
+    ///
+    /// Return `None` if there are no empty squares. If there is an empty
+    /// square or a missing value of a house without any options left, it is
+    /// returned as a [Branch] without alternatives.
+    pub(crate) fn choose(&self, candidates: &[u16; NUM_SQUARES], value: impl Fn(usize) -> u32) -> Option<Branch> {
+        let mut best = None;
+        let mut best_count = u32::MAX;
+
+        for square in (0..NUM_SQUARES).filter(|&square| value(square) == 0) {
+            let count = candidates[square].count_ones();
+
+            if count < best_count {
+                best = Some(Branch::Square {
+                    square,
+                    values: candidates[square],
+                });
+                best_count = count;
+            }
+
+            if *self == Branching::FirstEmpty || count <= 1 {
+                return best;
+            }
+        }
+
+        if *self != Branching::FewestOptions || best.is_none() {
+            return best;
+        }
+
+        for house in House::all() {
+            let mut used = 0;
+            let mut options = [0u16; 9];
+
+            for (i, (x, y)) in house.squares().into_iter().enumerate() {
+                let square = x + y * 9;
+
+                match value(square) {
+                    0 => {
+                        for (value, flags) in options.iter_mut().enumerate() {
+                            *flags |= (candidates[square] >> value & 1) << i;
+                        }
+                    },
+                    placed => used |= 1 << (placed - 1),
+                }
+            }
+
+            for (value, &squares) in options.iter().enumerate() {
+                let count = squares.count_ones();
+
+                if used >> value & 1 == 0 && count < best_count {
+                    best = Some(Branch::House {
+                        house,
+                        value: value as u32 + 1,
+                        squares,
+                    });
+                    best_count = count;
+
+                    if count <= 1 {
+                        return best;
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+/// A place where a solver guesses, see [Branching::choose()].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Branch {
+    /// Try the `values` (as bitflags) on `square`.
+    Square {
+        square: usize,
+        values: u16,
+    },
+    /// Try `value` on the squares of `house`, bit `i` of `squares` for
+    /// `house.squares()[i]`.
+    House {
+        house: House,
+        value: u32,
+        squares: u16,
+    },
+}
+
+impl Branch {
+
+    /// Check if all squares, values and houses of this [Branch] are in
+    /// bounds.
+    pub(crate) fn is_in_bounds(&self) -> bool {
+        match *self {
+            Branch::Square { square, values } => square < NUM_SQUARES && values <= 0x1ff,
+            Branch::House { house, value, squares } => house.index() <= 8 && (1..=9).contains(&value) && squares <= 0x1ff,
+        }
+    }
+}
+
+/// Solves [Sudoku] puzzles with a configurable [Engine].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Solver {
+    engine: Engine,
+    branching: Branching,
+}
+
+impl Solver {
+
+    /// Initialize a new [Solver] using the default [Engine].
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    /// Set the [Engine] used by this [Solver].
+    pub fn engine(mut self, engine: Engine) -> Solver {
+        self.engine = engine;
+        self
+    }
+
+    /// Set the [Branching] strategy used by this [Solver].
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::solver::{Branching, Engine, Solver};
+    ///
+    /// // A puzzle that needs a lot of guessing, where the squares the solver
+    /// // guesses on make a difference
+    /// let sudoku: Sudoku = "94..5.8......34..9.......25159.7..4....5.6....6..4.59762.......3..72......4.8..32".parse().unwrap();
+    ///
+    /// let solver = Solver::new().engine(Engine::Backtracking).branching(Branching::FewestCandidates);
+    ///
+    /// assert_eq!(solver.find_solution(&sudoku), sudoku.find_solution());
+    /// ```
+    ///
+    /// This has no effect on [Engine::DancingLinks].
+    pub fn branching(mut self, branching: Branching) -> Solver {
+        self.branching = branching;
+        self
+    }
+
+    /// Find a solution for `sudoku`, see [Sudoku::find_solution()].
+    pub fn find_solution(&self, sudoku: &Sudoku) -> Option<Sudoku> {
+        self.find_all_solutions(sudoku).next()
+    }
+
+    /// Find all solutions for `sudoku`, see [Sudoku::find_all_solutions()].
+    pub fn find_all_solutions(&self, sudoku: &Sudoku) -> Solutions {
+        let engine = match self.engine {
+            Engine::Bitboard => EngineState::Bitboard(Bitboard::new(sudoku, self.branching)),
+            Engine::Backtracking => EngineState::Backtracking(AllSolutionsIterator::new(sudoku, self.branching)),
+            Engine::DancingLinks => EngineState::DancingLinks(Box::new(DancingLinks::new(sudoku))),
+        };
+
+        Solutions {
+            puzzle: *sudoku,
+            engine,
+        }
+    }
+
+    /// Return `true` if `sudoku` is solvable, see [Sudoku::is_solvable()].
+    pub fn is_solvable(&self, sudoku: &Sudoku) -> bool {
+        self.find_solution(sudoku).is_some()
+    }
+
+    /// Count the solutions of `sudoku`, but stop as soon as `limit` solutions
+    /// have been found. See [Sudoku::count_solutions()].
+    pub fn count_solutions(&self, sudoku: &Sudoku, limit: usize) -> SolutionCount {
+        match self.find_all_solutions(sudoku).take(limit.max(2)).count() {
+            0 => SolutionCount::None,
+            1 => SolutionCount::Unique,
+            count => SolutionCount::Multiple(count),
+        }
+    }
+}
+
+/// The [Iterator] returned by [Solver::find_all_solutions()] and
+/// [Sudoku::find_all_solutions()].
+///
+/// It owns a copy of the [Sudoku] it was created for, so it can be stored
+/// alongside the puzzle or moved to another thread.
+///
+/// ```
+/// use sudoku::Sudoku;
+///
+/// // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+/// let sudoku: Sudoku = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4".parse().unwrap();
+///
+/// let solutions = sudoku.find_all_solutions();
+///
+/// let worker = std::thread::spawn(move || solutions.count());
+///
+/// assert_eq!(worker.join().unwrap(), 3);
+/// ```
+#[derive(Clone)]
+pub struct Solutions {
+    puzzle: Sudoku,
+    engine: EngineState,
+}
+
+/// The state of the [Engine] used by [Solutions].
+#[derive(Clone)]
+enum EngineState {
+    Bitboard(Bitboard),
+    Backtracking(AllSolutionsIterator),
+    // This one is much larger than the others
+    DancingLinks(Box<DancingLinks>),
+}
+
+impl Solutions {
+
+    /// Save the progress of the search, so it can be continued later with
+    /// [Solutions::resume()].
+    ///
+    /// ```
+    /// use sudoku::Sudoku;
+    /// use sudoku::solver::{Solutions, SolutionsState};
+    ///
+    /// // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+    /// let sudoku: Sudoku = "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4".parse().unwrap();
+    ///
+    /// let mut solutions = sudoku.find_all_solutions();
+    /// let first = solutions.next().unwrap();
+    ///
+    /// let json = solutions.state().to_json();
+    ///
+    /// // ... later, maybe in another process
+    ///
+    /// let state = SolutionsState::from_json(&json).unwrap();
+    /// let resumed = Solutions::resume(state).unwrap();
+    ///
+    /// assert_eq!(resumed.count(), 2);
+    /// # assert!(first.is_solved());
+    /// ```
+    pub fn state(&self) -> SolutionsState {
+        let search = match &self.engine {
+            EngineState::Bitboard(solutions) => SearchState::Bitboard(Box::new(solutions.state())),
+            EngineState::Backtracking(solutions) => SearchState::Backtracking(solutions.state()),
+            EngineState::DancingLinks(solutions) => SearchState::DancingLinks(solutions.state()),
+        };
+
+        SolutionsState {
+            puzzle: self.puzzle,
+            search,
+        }
+    }
+
+    /// Continue a search saved with [Solutions::state()].
+    ///
+    /// The resumed [Solutions] return exactly the solutions the original
+    /// [Solutions] would have returned after the state was saved, as long as
+    /// the version of this crate stays the same.
+    ///
+    /// Return an error if `state` was modified and no longer describes a
+    /// search for the solutions of its puzzle.
+    pub fn resume(state: SolutionsState) -> Result<Solutions, StateError> {
+        let puzzle = state.puzzle;
+
+        let engine = match state.search {
+            SearchState::Bitboard(search) => EngineState::Bitboard(Bitboard::resume(&puzzle, *search)?),
+            SearchState::Backtracking(search) => EngineState::Backtracking(AllSolutionsIterator::resume(&puzzle, search)?),
+            SearchState::DancingLinks(search) => EngineState::DancingLinks(Box::new(DancingLinks::resume(&puzzle, search)?)),
+        };
+
+        Ok(Solutions { puzzle, engine })
+    }
+
+    /// Get the puzzle whose solutions are searched.
+    pub fn puzzle(&self) -> Sudoku {
+        self.puzzle
+    }
+
+    /// Get the [Engine] used to find the solutions.
+    pub fn engine(&self) -> Engine {
+        match self.engine {
+            EngineState::Bitboard(_) => Engine::Bitboard,
+            EngineState::Backtracking(_) => Engine::Backtracking,
+            EngineState::DancingLinks(_) => Engine::DancingLinks,
+        }
+    }
+}
+
+impl Iterator for Solutions {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Sudoku> {
+        match &mut self.engine {
+            EngineState::Bitboard(solutions) => solutions.next(),
+            EngineState::Backtracking(solutions) => solutions.next(),
+            EngineState::DancingLinks(solutions) => solutions.next(),
+        }
+    }
+}
+
+impl fmt::Debug for Solutions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Solutions")
+            .field("engine", &self.engine())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The progress of a [Solutions] iterator, see [Solutions::state()].
+///
+/// The state can be serialized with any [serde] format, or with
+/// [SolutionsState::to_json()] and [SolutionsState::to_bytes()].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SolutionsState {
+    puzzle: Sudoku,
+    search: SearchState,
+}
+
+/// The engine specific part of a [SolutionsState].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+enum SearchState {
+    // This one is much larger than the others
+    Bitboard(Box<BitboardState>),
+    Backtracking(BacktrackingState),
+    DancingLinks(DancingLinksState),
+}
+
+impl SolutionsState {
+
+    /// Serialize this state to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a SolutionsState can always be serialized")
+    }
+
+    /// Deserialize a state serialized with [SolutionsState::to_json()].
+    pub fn from_json(json: &str) -> Result<SolutionsState, StateError> {
+        serde_json::from_str(json).map_err(|error| StateError::Decode(error.to_string()))
+    }
+
+    /// Serialize this state to a compact binary format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("a SolutionsState can always be serialized")
+    }
+
+    /// Deserialize a state serialized with [SolutionsState::to_bytes()].
+    pub fn from_bytes(bytes: &[u8]) -> Result<SolutionsState, StateError> {
+        bincode::deserialize(bytes).map_err(|error| StateError::Decode(error.to_string()))
+    }
+}
+
+/// The error returned when a [SolutionsState] cannot be restored.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StateError {
+    /// The serialized state could not be decoded.
+    Decode(String),
+    /// The state was decoded, but is not consistent with its puzzle.
+    Inconsistent(&'static str),
+}
+
+impl From<&'static str> for StateError {
+    fn from(reason: &'static str) -> StateError {
+        StateError::Inconsistent(reason)
+    }
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Decode(error) => write!(f, "Cannot decode the state: {}", error),
+            StateError::Inconsistent(reason) => write!(f, "Inconsistent state: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+#[cfg(test)]
+mod tests {
+
+    use itertools::Itertools;
+
+    use crate::{House, SolutionCount, Sudoku, NUM_SQUARES};
+    use crate::tests::PUZZLE;
+
+    use super::{Branch, Branching, Engine, Solutions, SolutionsState, Solver, StateError};
+
+    const PUZZLES: [&str; 3] = [
+        PUZZLE,
+        // taken from https://math.stackexchange.com/questions/813444/sudoku-puzzle-with-exactly-3-solutions
+        "3.96..4.....7.9....87......75..6.23.6..9.4..8.28.5..41......59....196..7..6...1.4",
+        // taken from https://puzzling.stackexchange.com/questions/67789/examples-of-sudokus-with-two-solutions
+        "2957438614318659..8761925433874592166123874955492167387635241899286713541549386..",
+    ];
+
+    #[test]
+    fn engines_find_same_solutions() {
+        for puzzle in PUZZLES {
+            let sudoku: Sudoku = puzzle.parse().unwrap();
+
+            let solutions = Engine::ALL.map(|engine| {
+                Solver::new()
+                    .engine(engine)
+                    .find_all_solutions(&sudoku)
+                    .sorted_by_key(|solution| solution.grid)
+                    .collect::<Vec<_>>()
+            });
+
+            assert!(!solutions[0].is_empty());
+            assert!(solutions.iter().all_equal(), "{}", puzzle);
+        }
+    }
+
+    #[test]
+    fn branchings_find_same_solutions() {
+        for puzzle in PUZZLES {
+            let sudoku: Sudoku = puzzle.parse().unwrap();
+
+            for engine in [Engine::Bitboard, Engine::Backtracking] {
+                let solutions = Branching::ALL.map(|branching| {
+                    Solver::new()
+                        .engine(engine)
+                        .branching(branching)
+                        .find_all_solutions(&sudoku)
+                        .sorted_by_key(|solution| solution.grid)
+                        .collect::<Vec<_>>()
+                });
+
+                assert!(!solutions[0].is_empty());
+                assert!(solutions.iter().all_equal(), "{:?} {}", engine, puzzle);
+            }
+        }
+    }
+
+    #[test]
+    fn choose_hidden_single() {
+        // 1 is only possible on the first square of the first row, but every
+        // square has at least 2 candidates
+        let mut candidates = [0b11; NUM_SQUARES];
+        candidates[1..9].fill(0b110);
+
+        let branch = Branching::FewestOptions.choose(&candidates, |_| 0);
+        assert_eq!(branch, Some(Branch::House { house: House::Row(0), value: 1, squares: 0b1 }));
+
+        let branch = Branching::FewestCandidates.choose(&candidates, |_| 0);
+        assert_eq!(branch, Some(Branch::Square { square: 0, values: 0b11 }));
+    }
+
+    #[test]
+    fn choose_without_empty_squares() {
+        for branching in Branching::ALL {
+            assert_eq!(branching.choose(&[0; NUM_SQUARES], |_| 1), None);
+        }
+    }
+
+    #[test]
+    fn solutions_is_send_and_static() {
+        fn assert_send_static<T: Send + 'static>(_: &T) {}
+
+        for engine in Engine::ALL {
+            let solutions = {
+                let sudoku: Sudoku = PUZZLES[1].parse().unwrap();
+                Solver::new().engine(engine).find_all_solutions(&sudoku)
+            };
+
+            assert_send_static(&solutions);
+            assert_eq!(solutions.engine(), engine);
+            assert_eq!(std::thread::spawn(move || solutions.count()).join().unwrap(), 3);
+        }
+    }
+
+    #[test]
+    fn cloned_solutions_continue_independently() {
+        let sudoku: Sudoku = PUZZLES[1].parse().unwrap();
+
+        for engine in Engine::ALL {
+            let mut solutions = Solver::new().engine(engine).find_all_solutions(&sudoku);
+            let first = solutions.next();
+
+            let cloned = solutions.clone();
+
+            assert_eq!(solutions.collect::<Vec<_>>(), cloned.collect::<Vec<_>>());
+            assert!(first.is_some());
+        }
+    }
+
+    #[test]
+    fn resume_after_every_solution() {
+        let sudoku: Sudoku = PUZZLES[1].parse().unwrap();
+
+        for engine in Engine::ALL {
+            let all = Solver::new().engine(engine).find_all_solutions(&sudoku).collect::<Vec<_>>();
+
+            for skip in 0..=all.len() {
+                let mut solutions = Solver::new().engine(engine).find_all_solutions(&sudoku);
+                solutions.by_ref().take(skip).for_each(drop);
+
+                let json = Solutions::resume(SolutionsState::from_json(&solutions.state().to_json()).unwrap()).unwrap();
+                let bytes = Solutions::resume(SolutionsState::from_bytes(&solutions.state().to_bytes()).unwrap()).unwrap();
+
+                assert_eq!(json.engine(), engine);
+                assert_eq!(json.collect::<Vec<_>>(), all[skip..], "{:?}", engine);
+                assert_eq!(bytes.collect::<Vec<_>>(), all[skip..], "{:?}", engine);
+            }
+        }
+    }
+
+    #[test]
+    fn resume_midway_through_empty_grid() {
+        for engine in Engine::ALL {
+            let mut solutions = Solver::new().engine(engine).find_all_solutions(&Sudoku::new_empty());
+            solutions.by_ref().take(5).for_each(drop);
+
+            let resumed = Solutions::resume(solutions.state()).unwrap();
+
+            assert_eq!(resumed.take(20).collect::<Vec<_>>(), solutions.take(20).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn resume_rejects_inconsistent_state() {
+        let sudoku: Sudoku = PUZZLES[1].parse().unwrap();
+
+        for engine in Engine::ALL {
+            let mut solutions = Solver::new().engine(engine).find_all_solutions(&sudoku);
+            solutions.next();
+
+            // put the search onto a puzzle without empty squares
+            let puzzle = serde_json::to_string(&sudoku).unwrap();
+            let solved = serde_json::to_string(&solutions.clone().next().unwrap()).unwrap();
+            let json = solutions.state().to_json().replacen(&puzzle, &solved, 1);
+            let state = SolutionsState::from_json(&json).unwrap();
+
+            assert!(matches!(Solutions::resume(state), Err(StateError::Inconsistent(_))), "{:?}", engine);
+        }
+
+        assert!(matches!(SolutionsState::from_json("{}"), Err(StateError::Decode(_))));
+        assert!(matches!(SolutionsState::from_bytes(&[1, 2, 3]), Err(StateError::Decode(_))));
+    }
+
+    #[test]
+    fn engines_count_solutions() {
+        let sudoku: Sudoku = PUZZLES[1].parse().unwrap();
+
+        for engine in Engine::ALL {
+            let solver = Solver::new().engine(engine);
+
+            assert_eq!(solver.count_solutions(&sudoku, 10), SolutionCount::Multiple(3));
+            assert_eq!(solver.count_solutions(&Sudoku::new_empty(), 10), SolutionCount::Multiple(10));
+            assert!(solver.is_solvable(&sudoku));
+        }
+    }
+}